@@ -0,0 +1,227 @@
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use dialoguer::Password;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::config::Config;
+
+const SERVICE: &str = "ccmt";
+const ACCOUNT: &str = "anthropic-api-key";
+
+/// Resolve the API key: `auth.api_key`, then `auth.api_key_command`,
+/// then ANTHROPIC_API_KEY, then the OS keyring
+pub fn api_key(config: &Config) -> Result<String> {
+    resolve(
+        &config.auth.api_key,
+        &config.auth.api_key_command,
+        || std::env::var("ANTHROPIC_API_KEY").ok(),
+        keyring_get,
+    )
+}
+
+fn resolve(
+    key: &str,
+    command: &str,
+    env: impl FnOnce() -> Option<String>,
+    keyring: impl FnOnce() -> Option<String>,
+) -> Result<String> {
+    if !key.is_empty() {
+        return Ok(key.to_string());
+    }
+    if !command.is_empty() {
+        return run_key_command(command);
+    }
+    Ok(env()
+        .filter(|k| !k.is_empty())
+        .or_else(keyring)
+        .unwrap_or_default())
+}
+
+/// Run `auth.api_key_command` through the shell and use the first line it prints
+fn run_key_command(command: &str) -> Result<String> {
+    let output = shell(command)
+        .stderr(Stdio::inherit())
+        .output()
+        .with_context(|| format!("Failed to run auth.api_key_command `{command}`"))?;
+    if !output.status.success() {
+        bail!(
+            "auth.api_key_command `{command}` failed ({})",
+            output.status
+        );
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let key = stdout.lines().next().unwrap_or_default().trim().to_string();
+    if key.is_empty() {
+        bail!("auth.api_key_command `{command}` printed no key");
+    }
+    Ok(key)
+}
+
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+/// Read the key from the Secret Service (secret-tool) or macOS Keychain (security)
+fn keyring_get() -> Option<String> {
+    let mut cmd = if cfg!(target_os = "macos") {
+        let mut cmd = Command::new("security");
+        cmd.args(["find-generic-password", "-s", SERVICE, "-a", ACCOUNT, "-w"]);
+        cmd
+    } else {
+        let mut cmd = Command::new("secret-tool");
+        cmd.args(["lookup", "service", SERVICE, "account", ACCOUNT]);
+        cmd
+    };
+    let output = cmd.stderr(Stdio::null()).output().ok()?;
+    let key = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !key.is_empty()).then_some(key)
+}
+
+fn keyring_set(key: &str) -> Result<()> {
+    // Both tools read the secret from stdin, keeping it out of the process list
+    let (mut cmd, input) = if cfg!(target_os = "macos") {
+        let mut cmd = Command::new("security");
+        // A trailing -w without a value makes security prompt for the password, then a retype
+        cmd.args([
+            "add-generic-password",
+            "-U",
+            "-s",
+            SERVICE,
+            "-a",
+            ACCOUNT,
+            "-w",
+        ]);
+        (cmd, format!("{key}\n{key}\n"))
+    } else {
+        let mut cmd = Command::new("secret-tool");
+        cmd.args(["store", "--label=ccmt Anthropic API key"])
+            .args(["service", SERVICE, "account", ACCOUNT]);
+        (cmd, key.to_string())
+    };
+    let status = cmd
+        .stdin(Stdio::piped())
+        .spawn()
+        .and_then(|mut child| {
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin.write_all(input.as_bytes())?;
+            }
+            child.wait()
+        })
+        .context(keyring_missing())?;
+    if !status.success() {
+        bail!("Failed to store the API key in the keyring");
+    }
+    Ok(())
+}
+
+fn keyring_delete() -> Result<bool> {
+    let status = if cfg!(target_os = "macos") {
+        Command::new("security")
+            .args(["delete-generic-password", "-s", SERVICE, "-a", ACCOUNT])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+    } else {
+        Command::new("secret-tool")
+            .args(["clear", "service", SERVICE, "account", ACCOUNT])
+            .status()
+    }
+    .context(keyring_missing())?;
+    Ok(status.success())
+}
+
+fn keyring_missing() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "Failed to run `security`"
+    } else {
+        "Failed to run `secret-tool`. Install libsecret-tools (Debian/Ubuntu) or libsecret (Fedora/Arch)"
+    }
+}
+
+/// `ccmt auth login`: store the API key in the OS keyring
+pub fn login(config: &Config) -> Result<()> {
+    let key = Password::new()
+        .with_prompt("Anthropic API key")
+        .interact()?;
+    let key = key.trim();
+    if !key.starts_with("sk-ant-") {
+        eprintln!(
+            "{}",
+            "The key doesn't look like an Anthropic API key (sk-ant-...); storing it anyway."
+                .yellow()
+        );
+    }
+    keyring_set(key)?;
+    println!("Stored the API key in the keyring");
+    if config.auth.provider != "api" {
+        println!("Run `ccmt config set auth.provider api` to use it");
+    }
+    Ok(())
+}
+
+/// `ccmt auth logout`: remove the API key from the OS keyring
+pub fn logout() -> Result<()> {
+    if keyring_delete()? {
+        println!("Removed the API key from the keyring");
+    } else {
+        println!("No API key stored in the keyring");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_key_wins() {
+        let key = resolve(
+            "sk-config",
+            "echo sk-command",
+            || Some("sk-env".into()),
+            || None,
+        );
+        assert_eq!(key.unwrap(), "sk-config");
+    }
+
+    #[test]
+    fn command_comes_before_env_and_keyring() {
+        let key = resolve(
+            "",
+            "printf 'sk-command\\nignored'",
+            || Some("sk-env".into()),
+            || Some("sk-keyring".into()),
+        );
+        assert_eq!(key.unwrap(), "sk-command");
+    }
+
+    #[test]
+    fn env_then_keyring() {
+        let key = resolve(
+            "",
+            "",
+            || Some("sk-env".into()),
+            || Some("sk-keyring".into()),
+        );
+        assert_eq!(key.unwrap(), "sk-env");
+        let key = resolve("", "", || None, || Some("sk-keyring".into()));
+        assert_eq!(key.unwrap(), "sk-keyring");
+        assert_eq!(resolve("", "", || None, || None).unwrap(), "");
+    }
+
+    #[test]
+    fn failing_command_is_an_error() {
+        assert!(resolve("", "exit 3", || None, || None).is_err());
+        let err = resolve("", "true", || None, || None).unwrap_err();
+        assert!(err.to_string().ends_with("printed no key"));
+    }
+}
@@ -0,0 +1,332 @@
+use crate::config::Config;
+use crate::{format, git, prompt, provider};
+use anyhow::{Result, bail};
+use colored::Colorize;
+use std::path::Path;
+
+const UNRELEASED: &str = "## [Unreleased]";
+
+const CHANGELOG_HEADER: &str = "# Changelog\n\n\
+All notable changes to this project will be documented in this file.\n\n\
+The format is based on [Keep a Changelog](https://keepachangelog.com/en/1.1.0/).\n";
+
+/// A conventional commit reduced to what a changelog needs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub kind: String,
+    pub scope: Option<String>,
+    pub breaking: bool,
+    pub description: String,
+    pub hash: String,
+}
+
+/// Parse a commit message into an entry; `None` if it is not a conventional commit
+pub fn parse_commit(hash: &str, message: &str) -> Option<Entry> {
+    let subject = message.lines().next()?;
+    let header = format::parse_header(format::strip_gitmoji(subject))?;
+    let breaking_footer = message
+        .lines()
+        .any(|l| l.starts_with("BREAKING CHANGE:") || l.starts_with("BREAKING-CHANGE:"));
+    Some(Entry {
+        kind: header.kind.to_string(),
+        scope: header.scope.map(str::to_string),
+        breaking: header.breaking || breaking_footer,
+        description: header.description.to_string(),
+        hash: hash.to_string(),
+    })
+}
+
+/// Keep a Changelog section for a commit type; `None` for types that are not user-facing.
+/// Reverts are left out: they usually undo a change that never shipped.
+fn section_for(kind: &str) -> Option<&'static str> {
+    match kind {
+        "feat" => Some("Added"),
+        "fix" => Some("Fixed"),
+        "perf" | "refactor" => Some("Changed"),
+        "security" => Some("Security"),
+        "deprecate" => Some("Deprecated"),
+        _ => None,
+    }
+}
+
+const SECTION_ORDER: &[&str] = &[
+    "Added",
+    "Changed",
+    "Deprecated",
+    "Removed",
+    "Fixed",
+    "Security",
+];
+
+/// Entries that will appear in the rendered changelog. Breaking changes are
+/// always included; other non-user-facing types are dropped.
+pub fn visible_entries(entries: &[Entry]) -> Vec<Entry> {
+    entries
+        .iter()
+        .filter(|e| e.breaking || section_for(&e.kind).is_some())
+        .cloned()
+        .collect()
+}
+
+/// Render a Keep a Changelog section. `version` of `None` renders `[Unreleased]`.
+pub fn render(entries: &[Entry], version: Option<&str>, date: &str) -> String {
+    let mut out = match version {
+        Some(v) => format!("## [{v}] - {date}\n"),
+        None => format!("{UNRELEASED}\n"),
+    };
+
+    for section in SECTION_ORDER {
+        let items: Vec<&Entry> = entries
+            .iter()
+            .filter(|e| section_for(&e.kind).unwrap_or("Changed") == *section)
+            .collect();
+        if items.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n### {section}\n\n"));
+        for e in items {
+            let breaking = if e.breaking { "**BREAKING:** " } else { "" };
+            let scope = e
+                .scope
+                .as_ref()
+                .map(|s| format!("**{s}:** "))
+                .unwrap_or_default();
+            let short = &e.hash[..e.hash.len().min(7)];
+            out.push_str(&format!("- {breaking}{scope}{} ({short})\n", e.description));
+        }
+    }
+
+    out
+}
+
+/// Insert a rendered section above the newest release in an existing changelog
+///
+/// A new `[Unreleased]` section replaces the existing one; a release goes below it.
+pub fn prepend_section(existing: &str, section: &str) -> String {
+    if existing.trim().is_empty() {
+        return format!("{CHANGELOG_HEADER}\n{section}");
+    }
+    let headings = release_headings(existing);
+    let Some(&first) = headings.first() else {
+        return format!("{}\n\n{section}", existing.trim_end());
+    };
+    if !existing[first..].starts_with(UNRELEASED) {
+        return format!("{}{section}\n{}", &existing[..first], &existing[first..]);
+    }
+    let end = headings.get(1).copied().unwrap_or(existing.len());
+    let (head, rest) = (&existing[..first], &existing[end..]);
+    let sep = if rest.is_empty() { "" } else { "\n" };
+    if section.starts_with(UNRELEASED) {
+        format!("{head}{section}{sep}{rest}")
+    } else {
+        let unreleased = existing[first..end].trim_end();
+        format!("{head}{unreleased}\n\n{section}{sep}{rest}")
+    }
+}
+
+/// Byte offsets of the `## ` release headings
+fn release_headings(text: &str) -> Vec<usize> {
+    let mut offset = 0;
+    let mut out = Vec::new();
+    for line in text.split_inclusive('\n') {
+        if line.starts_with("## ") {
+            out.push(offset);
+        }
+        offset += line.len();
+    }
+    out
+}
+
+/// Split `from..to` or `from...to` into its parts; a missing end is HEAD
+fn split_range(range: &str) -> (&str, &str, &str) {
+    for dots in ["...", ".."] {
+        if let Some((from, to)) = range.split_once(dots) {
+            return (from, dots, if to.is_empty() { "HEAD" } else { to });
+        }
+    }
+    (range, "..", "HEAD")
+}
+
+/// Version label for a ref: tags like `v1.2.0` become `1.2.0`, HEAD is unreleased
+fn version_label(rev: &str) -> Option<String> {
+    if rev == "HEAD" {
+        return None;
+    }
+    let v = rev
+        .strip_prefix('v')
+        .filter(|r| r.starts_with(|c: char| c.is_ascii_digit()));
+    Some(v.unwrap_or(rev).to_string())
+}
+
+fn polish(config: &Config, entries: &mut [Entry]) -> Result<()> {
+    let provider = provider::from_config(config)?;
+    let lines: Vec<String> = entries
+        .iter()
+        .map(|e| format!("- {}: {}", e.kind, e.description))
+        .collect();
+    let system = prompt::build_changelog_system_prompt(config);
+    let user = prompt::build_changelog_prompt(&lines);
+
+    eprintln!("{}", "Polishing changelog entries...".dimmed());
+    let raw = provider.generate(&user, &system, &config.auth.model)?;
+    let lines: Vec<&str> = raw
+        .lines()
+        .filter_map(|l| l.trim().strip_prefix("- "))
+        .collect();
+    if lines.len() != entries.len() {
+        eprintln!(
+            "{}",
+            "Polished output did not match the entry count; keeping original entries.".yellow()
+        );
+        return Ok(());
+    }
+    for (entry, line) in entries.iter_mut().zip(lines) {
+        entry.description = line.trim().to_string();
+    }
+    Ok(())
+}
+
+pub fn run(
+    config: &Config,
+    range: &str,
+    polish_entries: bool,
+    prepend: Option<&Path>,
+) -> Result<()> {
+    let (from, dots, to) = split_range(range);
+
+    let commits = git::git_log_commits(&format!("{from}{dots}{to}"))?;
+    let entries: Vec<Entry> = commits
+        .iter()
+        .filter_map(|(hash, msg)| parse_commit(hash, msg))
+        .collect();
+    let mut entries = visible_entries(&entries);
+    if entries.is_empty() {
+        bail!("No user-facing conventional commits found in {from}{dots}{to}.");
+    }
+
+    if polish_entries {
+        polish(config, &mut entries)?;
+    }
+
+    let date = git::git_commit_date(to)?;
+    let section = render(&entries, version_label(to).as_deref(), &date);
+
+    match prepend {
+        Some(path) => {
+            let existing = std::fs::read_to_string(path).unwrap_or_default();
+            std::fs::write(path, prepend_section(&existing, &section))?;
+            eprintln!("Updated {}", path.display());
+        }
+        None => print!("{section}"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(msg: &str) -> Entry {
+        parse_commit("abcdef1234", msg).unwrap()
+    }
+
+    #[test]
+    fn parses_breaking_marker_and_footer() {
+        assert!(entry("feat(api)!: drop v1").breaking);
+        assert!(entry("fix: x\n\nBREAKING CHANGE: config moved").breaking);
+        assert!(!entry("fix: x").breaking);
+        assert!(parse_commit("abc", "Update readme").is_none());
+    }
+
+    #[test]
+    fn drops_non_user_facing_types() {
+        let entries = vec![entry("feat: a"), entry("chore: b"), entry("ci!: c")];
+        let visible = visible_entries(&entries);
+        assert_eq!(visible.len(), 2);
+        assert_eq!(visible[1].kind, "ci");
+    }
+
+    #[test]
+    fn renders_sections_in_order() {
+        let entries = vec![entry("fix(ui): align header"), entry("feat: add login")];
+        let out = render(&entries, Some("1.2.0"), "2024-05-01");
+        assert!(out.starts_with("## [1.2.0] - 2024-05-01\n"));
+        let added = out.find("### Added").unwrap();
+        let fixed = out.find("### Fixed").unwrap();
+        assert!(added < fixed);
+        assert!(out.contains("- **ui:** align header (abcdef1)"));
+    }
+
+    #[test]
+    fn renders_unreleased_and_breaking() {
+        let out = render(&[entry("feat!: new config format")], None, "");
+        assert!(out.starts_with("## [Unreleased]\n"));
+        assert!(out.contains("- **BREAKING:** new config format"));
+    }
+
+    #[test]
+    fn prepends_above_latest_release() {
+        let existing = "# Changelog\n\nIntro.\n\n## [1.0.0] - 2024-01-01\n\n- old\n";
+        let out = prepend_section(existing, "## [1.1.0] - 2024-02-01\n\n- new\n");
+        let new = out.find("[1.1.0]").unwrap();
+        let old = out.find("[1.0.0]").unwrap();
+        assert!(out.starts_with("# Changelog\n\nIntro.\n"));
+        assert!(new < old);
+    }
+
+    #[test]
+    fn prepends_above_a_heading_on_the_first_line() {
+        let existing = "## [1.0.0] - 2024-01-01\n\n- old\n";
+        let out = prepend_section(existing, "## [1.1.0] - 2024-02-01\n\n- new\n");
+        assert_eq!(
+            out,
+            "## [1.1.0] - 2024-02-01\n\n- new\n\n## [1.0.0] - 2024-01-01\n\n- old\n"
+        );
+    }
+
+    #[test]
+    fn replaces_the_unreleased_section() {
+        let existing =
+            "# Changelog\n\n## [Unreleased]\n\n- stale\n\n## [1.0.0] - 2024-01-01\n\n- old\n";
+        let out = prepend_section(existing, "## [Unreleased]\n\n- fresh\n");
+        assert_eq!(
+            out,
+            "# Changelog\n\n## [Unreleased]\n\n- fresh\n\n## [1.0.0] - 2024-01-01\n\n- old\n"
+        );
+        assert_eq!(prepend_section(&out, "## [Unreleased]\n\n- fresh\n"), out);
+
+        // A release goes below the pending Unreleased section
+        let out = prepend_section(existing, "## [1.1.0] - 2024-02-01\n\n- new\n");
+        assert_eq!(
+            out,
+            "# Changelog\n\n## [Unreleased]\n\n- stale\n\n## [1.1.0] - 2024-02-01\n\n- new\n\n## [1.0.0] - 2024-01-01\n\n- old\n"
+        );
+    }
+
+    #[test]
+    fn creates_header_for_new_file() {
+        let out = prepend_section("", "## [Unreleased]\n");
+        assert!(out.starts_with("# Changelog"));
+        assert!(out.ends_with("## [Unreleased]\n"));
+    }
+
+    #[test]
+    fn splits_two_and_three_dot_ranges() {
+        assert_eq!(split_range("v1..v2"), ("v1", "..", "v2"));
+        assert_eq!(split_range("v1...v2"), ("v1", "...", "v2"));
+        assert_eq!(split_range("v1.."), ("v1", "..", "HEAD"));
+        assert_eq!(split_range("v1"), ("v1", "..", "HEAD"));
+    }
+
+    #[test]
+    fn reverts_are_left_out() {
+        assert!(visible_entries(&[entry("revert: feat: add cache")]).is_empty());
+    }
+
+    #[test]
+    fn version_labels() {
+        assert_eq!(version_label("v1.2.0").as_deref(), Some("1.2.0"));
+        assert_eq!(version_label("release-3").as_deref(), Some("release-3"));
+        assert_eq!(version_label("HEAD"), None);
+    }
+}
@@ -1,4 +1,6 @@
 use clap::{Parser, Subcommand};
+use std::ffi::OsString;
+use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(name = "ccmt", about = "AI-powered commit message generator")]
@@ -7,32 +9,71 @@ pub struct Cli {
     pub command: Option<Commands>,
 
     /// Context hint for the AI
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub message: Option<String>,
 
     /// Generate message only, don't commit
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub dry_run: bool,
 
     /// Push after commit
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub push: bool,
 
     /// Skip confirmation prompt
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub no_confirm: bool,
 
     /// Override auth provider (cli or api)
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub provider: Option<String>,
 
     /// Override model
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub model: Option<String>,
 
     /// Override language (en or ja)
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub language: Option<String>,
+
+    /// Config profile to use (default: CCMT_PROFILE, or the profile matching the repository)
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
+    /// Add a Signed-off-by trailer
+    #[arg(long, global = true)]
+    pub signoff: bool,
+
+    /// Add a Co-authored-by trailer (repeatable)
+    #[arg(long = "co-author", value_name = "NAME <EMAIL>", global = true)]
+    pub co_authors: Vec<String>,
+
+    /// GPG-sign the commit (optionally with a specific key: -S<KEYID> or --gpg-sign=<KEYID>)
+    #[arg(
+        short = 'S',
+        long,
+        value_name = "KEYID",
+        num_args = 0..=1,
+        require_equals = true,
+        global = true
+    )]
+    pub gpg_sign: Option<Option<String>>,
+
+    /// Bypass pre-commit and commit-msg hooks
+    #[arg(long, global = true)]
+    pub no_verify: bool,
+
+    /// Override the commit author
+    #[arg(long, value_name = "AUTHOR", global = true)]
+    pub author: Option<String>,
+
+    /// Override the author date
+    #[arg(long, value_name = "DATE", global = true)]
+    pub date: Option<String>,
+
+    /// Extra arguments passed to `git commit` (after `--`)
+    #[arg(last = true, value_name = "GIT_ARGS")]
+    pub git_args: Vec<String>,
 }
 
 #[derive(Subcommand, Debug)]
@@ -47,20 +88,229 @@ pub enum Commands {
         #[command(subcommand)]
         action: HookAction,
     },
+    /// Manage the API key stored in the OS keyring
+    Auth {
+        #[command(subcommand)]
+        action: AuthAction,
+    },
+    /// Squash base..HEAD into one commit with a synthesized message
+    Squash {
+        /// Commit to squash onto (exclusive)
+        base: String,
+    },
+    /// Generate a pull request title and description for the current branch
+    Pr {
+        /// Branch the PR will be merged into (default: origin's default branch)
+        #[arg(long)]
+        base: Option<String>,
+        /// Output format
+        #[arg(long, default_value = "text", value_parser = ["text", "json"])]
+        format: String,
+    },
+    /// Generate a Keep a Changelog section from conventional commits
+    Changelog {
+        /// Commit range, e.g. v1.0.0..v1.1.0 (defaults to <from>..HEAD)
+        range: String,
+        /// Ask the AI to rewrite entries in user-facing language
+        #[arg(long)]
+        polish: bool,
+        /// Prepend the section into this file instead of printing it
+        #[arg(long, value_name = "FILE")]
+        prepend: Option<PathBuf>,
+    },
+    /// Check commit messages against the configured rules
+    Lint {
+        /// Commit or range to check, e.g. main..HEAD (default: the last commit)
+        #[arg(conflicts_with = "file")]
+        range: Option<String>,
+        /// Check a message file instead (e.g. from a commit-msg hook)
+        #[arg(long, value_name = "PATH")]
+        file: Option<PathBuf>,
+        /// Rewrite a failing message file with the AI so it conforms
+        #[arg(long, requires = "file")]
+        repair: bool,
+        /// Output format
+        #[arg(long, default_value = "text", value_parser = ["text", "json"])]
+        format: String,
+    },
+    /// Suggest the next semantic version and write an annotated tag message
+    Release {
+        /// Create the annotated tag (signed when -S is given)
+        #[arg(long)]
+        tag: bool,
+    },
 }
 
 #[derive(Subcommand, Debug)]
 pub enum ConfigAction {
-    /// Generate default global config
-    Init,
+    /// Set up the global config interactively (or the project's with --project)
+    Init {
+        /// Write a .ccmt.toml with only the settings that differ from the global config
+        #[arg(long)]
+        project: bool,
+    },
     /// Show merged config
-    Show,
+    Show {
+        /// Annotate each key with the layer it came from
+        #[arg(long)]
+        origin: bool,
+    },
+    /// Validate the global and project config files
+    Check,
+    /// Print the merged value of a key, e.g. commit.language
+    Get { key: String },
+    /// Set a key in the global config (or the project's with --project)
+    Set {
+        key: String,
+        value: String,
+        /// Write to the project's .ccmt.toml
+        #[arg(long, conflicts_with = "global")]
+        project: bool,
+        /// Write to the global config (default)
+        #[arg(long)]
+        global: bool,
+    },
+    /// Remove a key from the global config (or the project's with --project)
+    Unset {
+        key: String,
+        /// Remove from the project's .ccmt.toml
+        #[arg(long, conflicts_with = "global")]
+        project: bool,
+        /// Remove from the global config (default)
+        #[arg(long)]
+        global: bool,
+    },
+    /// Open the config in $EDITOR and validate it before saving
+    Edit {
+        /// Edit the project's .ccmt.toml
+        #[arg(long, conflicts_with = "global")]
+        project: bool,
+        /// Edit the global config (default)
+        #[arg(long)]
+        global: bool,
+    },
+    /// Print a JSON Schema for config files (for editor completion and validation)
+    Schema,
+    /// Upgrade an older config file to the current layout, keeping a backup
+    Migrate {
+        /// Migrate the project's .ccmt.toml
+        #[arg(long, conflicts_with = "global")]
+        project: bool,
+        /// Migrate the global config (default)
+        #[arg(long)]
+        global: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AuthAction {
+    /// Store an Anthropic API key in the keyring (Secret Service or macOS Keychain)
+    Login,
+    /// Remove the stored API key
+    Logout,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum HookAction {
     /// Install prepare-commit-msg hook
-    Install,
+    Install {
+        /// Hook to install
+        #[arg(long = "type", default_value = "prepare-commit-msg", value_parser = ["prepare-commit-msg", "commit-msg"])]
+        kind: String,
+        /// What the commit-msg hook does with failing messages
+        #[arg(long, default_value = "reject", value_parser = ["reject", "repair"])]
+        mode: String,
+        /// Run an existing hook before or after ccmt
+        #[arg(long, default_value = "after", value_parser = ["before", "after"])]
+        chain: String,
+        /// Write to the git hooks directory even when husky, lefthook or pre-commit is used
+        #[arg(long)]
+        native: bool,
+        /// Install for every repository through a global core.hooksPath
+        #[arg(long, conflicts_with = "native")]
+        global: bool,
+        /// With --global, use init.templateDir instead (applies to new clones and `git init`)
+        #[arg(long, requires = "global")]
+        template: bool,
+    },
     /// Remove installed hook
-    Remove,
+    Remove {
+        /// Hook to remove
+        #[arg(long = "type", default_value = "prepare-commit-msg", value_parser = ["prepare-commit-msg", "commit-msg"])]
+        kind: String,
+        /// Remove from the git hooks directory, ignoring hook managers
+        #[arg(long)]
+        native: bool,
+        /// Remove the hook installed with `hook install --global`
+        #[arg(long, conflicts_with = "native")]
+        global: bool,
+    },
+    /// Show where hooks are installed and whether they are up to date
+    Status,
+    /// Run hook logic (called by the installed hook scripts)
+    Run {
+        /// Hook being run
+        #[arg(value_parser = ["prepare-commit-msg", "commit-msg"])]
+        kind: String,
+        /// What commit-msg does with failing messages
+        #[arg(long, default_value = "reject", value_parser = ["reject", "repair"])]
+        mode: String,
+        /// Arguments git passed to the hook
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+}
+
+/// Rewrite git's attached `-S<KEYID>` into `--gpg-sign=<KEYID>`
+///
+/// The key needs `=` so that `ccmt -S squash main` doesn't read `squash` as a key,
+/// but git users type it attached out of habit.
+pub fn normalize_args(args: impl IntoIterator<Item = OsString>) -> Vec<OsString> {
+    let mut passthrough = false;
+    args.into_iter()
+        .map(|arg| {
+            if passthrough {
+                return arg;
+            }
+            if arg == "--" {
+                passthrough = true;
+                return arg;
+            }
+            match arg.to_str().and_then(|a| a.strip_prefix("-S")) {
+                Some(key) if !key.is_empty() && !key.starts_with('=') => {
+                    OsString::from(format!("--gpg-sign={key}"))
+                }
+                _ => arg,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Cli {
+        Cli::try_parse_from(normalize_args(args.iter().map(OsString::from))).unwrap()
+    }
+
+    #[test]
+    fn gpg_sign_accepts_git_and_equals_forms() {
+        assert_eq!(parse(&["ccmt", "-S"]).gpg_sign, Some(None));
+        let keyed = Some(Some("ABCDEF".to_string()));
+        assert_eq!(parse(&["ccmt", "-SABCDEF"]).gpg_sign, keyed);
+        assert_eq!(parse(&["ccmt", "-S=ABCDEF"]).gpg_sign, keyed);
+        assert_eq!(parse(&["ccmt", "--gpg-sign=ABCDEF"]).gpg_sign, keyed);
+
+        // A following word is never taken as the key
+        let cli = parse(&["ccmt", "-S", "squash", "main"]);
+        assert_eq!(cli.gpg_sign, Some(None));
+        assert!(matches!(cli.command, Some(Commands::Squash { .. })));
+    }
+
+    #[test]
+    fn git_args_are_left_alone() {
+        let args = normalize_args(["ccmt", "--", "-Sfoo"].map(OsString::from));
+        assert_eq!(args[2], "-Sfoo");
+    }
 }
@@ -0,0 +1,249 @@
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use dialoguer::{Confirm, Editor};
+use std::path::{Path, PathBuf};
+use toml_edit::DocumentMut;
+
+use super::validate::{self, Severity};
+use super::{Config, env, find_project_config, global_config_path};
+
+/// Which config file `config set`, `unset` and `edit` write to
+pub fn target_path(project: bool) -> Result<PathBuf> {
+    if !project {
+        return global_config_path();
+    }
+    let cwd = std::env::current_dir()?;
+    if let Some(path) = find_project_config(&cwd) {
+        return Ok(path);
+    }
+    let root = crate::git::git_toplevel().unwrap_or(cwd);
+    Ok(root.join(".ccmt.toml"))
+}
+
+/// Split "section.key" and check it names a config key, returning the key's default value
+fn lookup(key: &str) -> Result<(String, String, toml::Value)> {
+    if key == "version" {
+        bail!("`version` is managed by `ccmt config migrate` and can't be read or set here");
+    }
+    let defaults = toml::Table::try_from(Config::default())?;
+    let Some((section, name)) = key.split_once('.') else {
+        bail!("Key must be <section>.<key>, e.g. commit.language");
+    };
+    let unknown = |what: &str, candidates: Vec<String>| {
+        let full: Vec<&str> = candidates.iter().map(String::as_str).collect();
+        match validate::suggest(key, full.into_iter()) {
+            Some(s) => anyhow::anyhow!("Unknown {what} `{key}` (did you mean `{s}`?)"),
+            None => anyhow::anyhow!("Unknown {what} `{key}`"),
+        }
+    };
+    let all_keys = || {
+        defaults
+            .iter()
+            .filter_map(|(s, v)| v.as_table().map(|t| (s, t)))
+            .flat_map(|(s, t)| t.keys().map(move |k| format!("{s}.{k}")))
+            .collect::<Vec<_>>()
+    };
+    let Some(toml::Value::Table(keys)) = defaults.get(section) else {
+        return Err(unknown("section in", all_keys()));
+    };
+    let Some(default) = keys.get(name) else {
+        return Err(unknown("key", all_keys()));
+    };
+    Ok((section.to_string(), name.to_string(), default.clone()))
+}
+
+/// Print a key's merged value; strings are printed without quotes
+pub fn get(config: &Config, key: &str) -> Result<()> {
+    let (section, name, _) = lookup(key)?;
+    let table = toml::Table::try_from(config)?;
+    let value = &table[&section][&name];
+    match value {
+        toml::Value::String(s) => println!("{s}"),
+        other => println!("{other}"),
+    }
+    Ok(())
+}
+
+fn read_document(path: &Path) -> Result<DocumentMut> {
+    let content = if path.is_file() {
+        std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config: {}", path.display()))?
+    } else {
+        String::new()
+    };
+    content
+        .parse()
+        .with_context(|| format!("Failed to parse: {}", path.display()))
+}
+
+/// Validate and write, refusing contents with errors
+fn write_document(path: &Path, doc: &DocumentMut) -> Result<()> {
+    // Removing the first section leaves the next one's leading blank line
+    let content = doc.to_string().trim_start().to_string();
+    if let Some(problem) = validate::check(&content)
+        .into_iter()
+        .find(|p| p.severity == Severity::Error)
+    {
+        bail!("{}", problem.message);
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// Set `key` in the document, keeping its comments and formatting
+fn set_in(doc: &mut DocumentMut, section: &str, name: &str, value: &toml::Value) -> Result<()> {
+    let value: toml_edit::Value = value.to_string().parse()?;
+    let table = doc
+        .entry(section)
+        .or_insert_with(toml_edit::table)
+        .as_table_like_mut()
+        .with_context(|| format!("`{section}` is not a table"))?;
+    match table.get_mut(name).and_then(|item| item.as_value_mut()) {
+        // Keep the comment after the old value
+        Some(existing) => {
+            let decor = existing.decor().clone();
+            *existing = value;
+            *existing.decor_mut() = decor;
+        }
+        None => {
+            table.insert(name, toml_edit::value(value));
+        }
+    }
+    Ok(())
+}
+
+/// Remove `key`, dropping its section when nothing is left; false when it wasn't set
+fn unset_in(doc: &mut DocumentMut, section: &str, name: &str) -> bool {
+    let Some(table) = doc.get_mut(section).and_then(|t| t.as_table_like_mut()) else {
+        return false;
+    };
+    let removed = table.remove(name).is_some();
+    if table.is_empty() {
+        doc.remove(section);
+    }
+    removed
+}
+
+pub fn set(key: &str, value: &str, project: bool) -> Result<()> {
+    let (section, name, default) = lookup(key)?;
+    if project && key == "auth.api_key_command" {
+        bail!("auth.api_key_command is ignored in a project config; set it without --project");
+    }
+    let Some(parsed) = env::parse_value(value, &default) else {
+        bail!("Cannot parse `{value}` as {} for {key}", default.type_str());
+    };
+    let path = target_path(project)?;
+    let mut doc = read_document(&path)?;
+    set_in(&mut doc, &section, &name, &parsed)?;
+    write_document(&path, &doc)?;
+    println!("Set {key} = {parsed} in {}", path.display());
+    Ok(())
+}
+
+pub fn unset(key: &str, project: bool) -> Result<()> {
+    let (section, name, _) = lookup(key)?;
+    let path = target_path(project)?;
+    let mut doc = read_document(&path)?;
+    if !unset_in(&mut doc, &section, &name) {
+        println!("{key} is not set in {}", path.display());
+        return Ok(());
+    }
+    write_document(&path, &doc)?;
+    println!("Unset {key} in {}", path.display());
+    Ok(())
+}
+
+/// Open the config file in $EDITOR, re-validating before it is saved
+pub fn edit(project: bool) -> Result<()> {
+    let path = target_path(project)?;
+    let mut content = read_document(&path)?.to_string();
+    loop {
+        let Some(edited) = Editor::new().extension(".toml").edit(&content)? else {
+            println!("No changes made");
+            return Ok(());
+        };
+        let problems = validate::check(&edited);
+        for problem in &problems {
+            eprintln!("{}: {problem}", path.display());
+        }
+        if problems.iter().all(|p| p.severity != Severity::Error) {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, edited)?;
+            println!("Saved {}", path.display());
+            return Ok(());
+        }
+        let again = Confirm::new()
+            .with_prompt("The config has errors. Edit again?")
+            .default(true)
+            .interact()?;
+        if !again {
+            eprintln!("{}", "Discarded changes.".yellow());
+            return Ok(());
+        }
+        content = edited;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_suggests_keys() {
+        assert!(lookup("commit.language").is_ok());
+        let err = lookup("commit.languge").unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "Unknown key `commit.languge` (did you mean `commit.language`?)"
+        );
+        assert!(lookup("language").is_err());
+        let err = lookup("version").unwrap_err().to_string();
+        assert!(err.contains("ccmt config migrate"), "{err}");
+    }
+
+    #[test]
+    fn set_preserves_comments_and_formatting() {
+        let mut doc: DocumentMut =
+            "# my settings\n[commit]\nlanguage = \"en\"  # team default\nemoji   = false\n"
+                .parse()
+                .unwrap();
+        set_in(
+            &mut doc,
+            "commit",
+            "language",
+            &toml::Value::String("ja".into()),
+        )
+        .unwrap();
+        set_in(
+            &mut doc,
+            "auth",
+            "model",
+            &toml::Value::String("haiku".into()),
+        )
+        .unwrap();
+        assert_eq!(
+            doc.to_string(),
+            "# my settings\n[commit]\nlanguage = \"ja\"  # team default\nemoji   = false\n\n[auth]\nmodel = \"haiku\"\n"
+        );
+    }
+
+    #[test]
+    fn unset_removes_key_and_empty_section() {
+        let mut doc: DocumentMut =
+            "[auth]\nmodel = \"haiku\"\n\n[commit]\nemoji = true\nlanguage = \"ja\"\n"
+                .parse()
+                .unwrap();
+        assert!(unset_in(&mut doc, "commit", "emoji"));
+        assert!(unset_in(&mut doc, "auth", "model"));
+        assert!(!unset_in(&mut doc, "auth", "model"));
+        assert_eq!(
+            doc.to_string().trim_start(),
+            "[commit]\nlanguage = \"ja\"\n"
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use anyhow::{Result, bail};
+
+use super::validate::{self, Severity};
+use super::{Config, PartialConfig};
+
+/// Environment variable for a config key, e.g. `CCMT_COMMIT_EMOJI` for `commit.emoji`
+pub fn var_name(section: &str, key: &str) -> String {
+    format!("CCMT_{}_{}", section.to_uppercase(), key.to_uppercase())
+}
+
+/// Read `CCMT_<SECTION>_<KEY>` overrides, returning the partial config and
+/// the ("section.key", variable) pairs that were set
+pub fn load(
+    var: impl Fn(&str) -> Option<String>,
+) -> Result<(PartialConfig, Vec<(String, String)>)> {
+    // Known keys and their types come from the serialized defaults
+    let defaults = toml::Table::try_from(Config::default())?;
+    let mut table = toml::Table::new();
+    let mut set = Vec::new();
+
+    for (section, keys) in &defaults {
+        let toml::Value::Table(keys) = keys else {
+            continue;
+        };
+        for (key, default) in keys {
+            let name = var_name(section, key);
+            let Some(raw) = var(&name) else {
+                continue;
+            };
+            let Some(value) = parse_value(&raw, default) else {
+                bail!("{name}: cannot parse `{raw}` as {}", default.type_str());
+            };
+
+            let mut single = toml::Table::new();
+            single.insert(key.clone(), value.clone());
+            let mut wrapped = toml::Table::new();
+            wrapped.insert(section.clone(), toml::Value::Table(single));
+            if let Some(problem) = validate::check(&toml::to_string(&wrapped)?)
+                .into_iter()
+                .find(|p| p.severity == Severity::Error)
+            {
+                bail!("{name}: {}", problem.message);
+            }
+
+            if let toml::Value::Table(t) = table
+                .entry(section.clone())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            {
+                t.insert(key.clone(), value);
+            }
+            set.push((format!("{section}.{key}"), name));
+        }
+    }
+
+    Ok((table.try_into()?, set))
+}
+
+/// Parse a variable according to the type of the key's default value
+pub fn parse_value(raw: &str, default: &toml::Value) -> Option<toml::Value> {
+    match default {
+        toml::Value::String(_) => Some(toml::Value::String(raw.to_string())),
+        toml::Value::Boolean(_) => match raw.trim().to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Some(toml::Value::Boolean(true)),
+            "false" | "0" | "no" | "off" => Some(toml::Value::Boolean(false)),
+            _ => None,
+        },
+        toml::Value::Integer(_) => raw.trim().parse().ok().map(toml::Value::Integer),
+        // A TOML array, or a comma-separated list
+        toml::Value::Array(_) => {
+            let raw = raw.trim();
+            if raw.starts_with('[') {
+                let table: toml::Table = toml::from_str(&format!("v = {raw}")).ok()?;
+                table.get("v").cloned()
+            } else {
+                Some(toml::Value::Array(
+                    raw.split(',')
+                        .map(str::trim)
+                        .filter(|s| !s.is_empty())
+                        .map(|s| toml::Value::String(s.to_string()))
+                        .collect(),
+                ))
+            }
+        }
+        // An inline table, e.g. { deps = { description = "Dependency updates" } }
+        toml::Value::Table(_) => {
+            let table: toml::Table = toml::from_str(&format!("v = {}", raw.trim())).ok()?;
+            table.get("v").filter(|v| v.is_table()).cloned()
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn load_from(vars: &[(&str, &str)]) -> Result<(Config, Vec<(String, String)>)> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let (partial, set) = load(|name| vars.get(name).cloned())?;
+        let mut config = Config::default();
+        config.apply_partial(partial);
+        Ok((config, set))
+    }
+
+    #[test]
+    fn overrides_each_type() {
+        let (config, set) = load_from(&[
+            ("CCMT_COMMIT_EMOJI", "true"),
+            ("CCMT_AUTH_MODEL", "haiku"),
+            ("CCMT_PROMPT_MAX_DIFF_LENGTH", "2000"),
+            ("CCMT_COMMIT_SCOPES", "api, cli"),
+            ("CCMT_COMMIT_TICKET_PATTERNS", r#"['GH-\d+']"#),
+        ])
+        .unwrap();
+        assert!(config.commit.emoji);
+        assert_eq!(config.auth.model, "haiku");
+        assert_eq!(config.prompt.max_diff_length, 2000);
+        assert_eq!(config.commit.scopes.allowed(), ["api", "cli"]);
+        assert_eq!(config.commit.ticket_patterns, vec![r"GH-\d+"]);
+        assert!(set.contains(&("commit.emoji".to_string(), "CCMT_COMMIT_EMOJI".to_string())));
+        assert_eq!(set.len(), 5);
+    }
+
+    #[test]
+    fn types_and_inferred_scopes() {
+        let (config, _) = load_from(&[
+            (
+                "CCMT_COMMIT_TYPES",
+                r#"{ security = { description = "Security fixes" }, deps = { description = "Dependency updates", emoji = "⬆️" } }"#,
+            ),
+            ("CCMT_COMMIT_SCOPES", "infer"),
+        ])
+        .unwrap();
+        assert_eq!(
+            config.commit.types["deps"].description,
+            "Dependency updates"
+        );
+        let names: Vec<&str> = config.commit.types.keys().map(String::as_str).collect();
+        assert_eq!(names, ["security", "deps"]);
+        assert!(config.commit.scopes.infer());
+        assert!(config.commit.scopes.allowed().is_empty());
+    }
+
+    #[test]
+    fn unset_variables_change_nothing() {
+        let (config, set) = load_from(&[("CCMT_AMEND", "1")]).unwrap();
+        assert!(set.is_empty());
+        assert_eq!(config.commit.language, "en");
+    }
+
+    #[test]
+    fn bad_values_name_the_variable() {
+        let err = load_from(&[("CCMT_COMMIT_EMOJI", "maybe")]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "CCMT_COMMIT_EMOJI: cannot parse `maybe` as boolean"
+        );
+        let err = load_from(&[("CCMT_AUTH_PROVIDER", "apii")]).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("CCMT_AUTH_PROVIDER: unknown value `apii`")
+        );
+    }
+}
@@ -0,0 +1,323 @@
+use anyhow::{Result, bail};
+use colored::Colorize;
+use dialoguer::{Confirm, Input, Select};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use super::validate::{MODEL_ALIASES, PROVIDERS};
+use super::{Config, edit, global_config_path, load_partial};
+
+/// What the wizard found on this machine
+struct Detected {
+    /// Path of the `claude` executable
+    claude: Option<PathBuf>,
+    /// Whether ANTHROPIC_API_KEY is set
+    env_key: bool,
+}
+
+impl Detected {
+    fn probe() -> Self {
+        Self {
+            claude: std::env::var_os("PATH").and_then(|path| find_in_path("claude", &path)),
+            env_key: std::env::var("ANTHROPIC_API_KEY").is_ok_and(|k| !k.trim().is_empty()),
+        }
+    }
+
+    /// The CLI when it's installed, else the API when a key is already available
+    fn provider(&self) -> &'static str {
+        match (&self.claude, self.env_key) {
+            (None, true) => "api",
+            _ => "cli",
+        }
+    }
+
+    fn print(&self) {
+        match &self.claude {
+            Some(path) => println!("{} claude CLI found at {}", "✓".green(), path.display()),
+            None => println!("{} claude CLI not found on PATH", "✗".red()),
+        }
+        if self.env_key {
+            println!("{} ANTHROPIC_API_KEY is set", "✓".green());
+        } else {
+            println!("{} ANTHROPIC_API_KEY is not set", "✗".red());
+        }
+        println!();
+    }
+}
+
+fn find_in_path(program: &str, path: &OsStr) -> Option<PathBuf> {
+    std::env::split_paths(path)
+        .map(|dir| dir.join(program))
+        .find(|p| p.is_file())
+}
+
+/// `ccmt config init`: walk through the main settings and write the global config,
+/// or with `project` a `.ccmt.toml` holding only what differs from the global config
+pub fn init(project: bool, interactive: bool) -> Result<()> {
+    let path = edit::target_path(project)?;
+    if path.is_file() {
+        let overwrite = interactive
+            && Confirm::new()
+                .with_prompt(format!("{} already exists. Overwrite it?", path.display()))
+                .default(false)
+                .interact()?;
+        if !overwrite {
+            bail!(
+                "Config already exists at {}. Delete it first to re-initialize.",
+                path.display()
+            );
+        }
+    }
+
+    let base = if project {
+        global_config()?
+    } else {
+        Config::default()
+    };
+    let detected = Detected::probe();
+    let config = if interactive {
+        detected.print();
+        let Some(config) = ask(&base, &detected, project)? else {
+            eprintln!("{}", "Cancelled.".yellow());
+            return Ok(());
+        };
+        config
+    } else if project {
+        bail!("`config init --project` asks which settings to override; run it in a terminal");
+    } else {
+        let mut config = base.clone();
+        config.auth.provider = detected.provider().to_string();
+        config
+    };
+
+    let content = if project {
+        let overrides = overrides(&base, &config)?;
+        if overrides.is_empty() {
+            println!("Nothing differs from the global config; no file written");
+            return Ok(());
+        }
+        toml::to_string_pretty(&overrides)?
+    } else {
+        global_content(&config)?
+    };
+    write(&path, &content)?;
+    println!("Created config at {}", path.display());
+    Ok(())
+}
+
+/// Defaults with the global config file applied
+fn global_config() -> Result<Config> {
+    let mut config = Config::default();
+    let path = global_config_path()?;
+    if path.is_file() {
+        config.apply_partial(load_partial(&path)?.0);
+    }
+    Ok(config)
+}
+
+/// Ask for each setting, starting from `base`; None when the user backs out
+fn ask(base: &Config, detected: &Detected, project: bool) -> Result<Option<Config>> {
+    let mut config = base.clone();
+
+    let current = if project {
+        config.auth.provider.as_str()
+    } else {
+        detected.provider()
+    };
+    let choices = [
+        "cli - Claude CLI (uses your Claude Code login)",
+        "api - Anthropic API (needs an API key)",
+    ];
+    let Some(choice) = Select::new()
+        .with_prompt("Provider")
+        .items(&choices)
+        .default(PROVIDERS.iter().position(|p| *p == current).unwrap_or(0))
+        .interact_opt()?
+    else {
+        return Ok(None);
+    };
+    config.auth.provider = PROVIDERS[choice].to_string();
+
+    // Keys never go in a project file, and an existing source is left alone
+    let needs_key = config.auth.provider == "api"
+        && !project
+        && !detected.env_key
+        && config.auth.api_key_command.is_empty();
+    if needs_key {
+        let choices = [
+            "Store it in the OS keyring now",
+            "Run a command that prints it (api_key_command)",
+            "Set ANTHROPIC_API_KEY later",
+        ];
+        match Select::new()
+            .with_prompt("How should ccmt get the API key?")
+            .items(&choices)
+            .default(0)
+            .interact_opt()?
+        {
+            Some(0) => crate::auth::login(&config)?,
+            Some(1) => {
+                config.auth.api_key_command = Input::new()
+                    .with_prompt("Command, e.g. pass show anthropic")
+                    .interact_text()?;
+            }
+            Some(_) => {}
+            None => return Ok(None),
+        }
+    }
+
+    let mut models: Vec<&str> = MODEL_ALIASES.to_vec();
+    if !models.contains(&base.auth.model.as_str()) {
+        models.push(&base.auth.model);
+    }
+    let Some(choice) = Select::new()
+        .with_prompt("Model")
+        .items(&models)
+        .default(
+            models
+                .iter()
+                .position(|m| *m == base.auth.model)
+                .unwrap_or(0),
+        )
+        .interact_opt()?
+    else {
+        return Ok(None);
+    };
+    config.auth.model = models[choice].to_string();
+
+    config.commit.language = Input::new()
+        .with_prompt("Commit message language (e.g. en, ja)")
+        .default(base.commit.language.clone())
+        .validate_with(|v: &String| {
+            if v.trim().is_empty() || v.contains(char::is_whitespace) {
+                Err("Enter a language code such as en or ja")
+            } else {
+                Ok(())
+            }
+        })
+        .interact_text()?;
+    config.commit.conventional = Confirm::new()
+        .with_prompt("Use Conventional Commits (feat:, fix:, ...)?")
+        .default(base.commit.conventional)
+        .interact()?;
+    config.commit.emoji = Confirm::new()
+        .with_prompt("Prefix messages with Gitmoji?")
+        .default(base.commit.emoji)
+        .interact()?;
+
+    let test = Confirm::new()
+        .with_prompt("Send a short test request to check the provider?")
+        .default(true)
+        .interact()?;
+    if test && let Err(e) = test_provider(&config) {
+        eprintln!("{} {e:#}", "Provider test failed:".red());
+        let save = Confirm::new()
+            .with_prompt("Save the config anyway?")
+            .default(true)
+            .interact()?;
+        if !save {
+            return Ok(None);
+        }
+    }
+    Ok(Some(config))
+}
+
+fn test_provider(config: &Config) -> Result<()> {
+    eprintln!("{}", "Sending a test request...".dimmed());
+    let provider = crate::provider::from_config(config)?;
+    let reply = provider.generate("Reply with the single word OK.", "", &config.auth.model)?;
+    println!(
+        "{} {} replied: {}",
+        "✓".green(),
+        config.auth.provider,
+        reply.lines().next().unwrap_or_default().trim()
+    );
+    Ok(())
+}
+
+/// The global config file, without the plaintext key
+fn global_content(config: &Config) -> Result<String> {
+    // Keys belong in the keyring or api_key_command, not in plaintext
+    let mut doc: toml_edit::DocumentMut = toml::to_string_pretty(config)?.parse()?;
+    if let Some(auth) = doc.get_mut("auth").and_then(|a| a.as_table_like_mut()) {
+        auth.remove("api_key");
+    }
+    Ok(doc.to_string())
+}
+
+/// Sections and keys whose value in `config` differs from `base`
+fn overrides(base: &Config, config: &Config) -> Result<toml::Table> {
+    let base = toml::Table::try_from(base)?;
+    let mut out = toml::Table::new();
+    for (section, value) in toml::Table::try_from(config)? {
+        let toml::Value::Table(keys) = value else {
+            continue;
+        };
+        let changed: toml::Table = keys
+            .into_iter()
+            .filter(|(key, value)| base.get(&section).and_then(|s| s.get(key)) != Some(value))
+            .collect();
+        if !changed.is_empty() {
+            out.insert(section, toml::Value::Table(changed));
+        }
+    }
+    Ok(out)
+}
+
+fn write(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_keep_only_changed_keys() {
+        let base = Config::default();
+        let mut config = base.clone();
+        config.commit.language = "ja".to_string();
+        config.commit.emoji = true;
+        let table = overrides(&base, &config).unwrap();
+        assert_eq!(
+            toml::to_string(&table).unwrap(),
+            "[commit]\nemoji = true\nlanguage = \"ja\"\n"
+        );
+        assert!(overrides(&base, &base).unwrap().is_empty());
+    }
+
+    #[test]
+    fn provider_follows_what_is_installed() {
+        let detected = |claude: bool, env_key: bool| Detected {
+            claude: claude.then(|| PathBuf::from("/usr/bin/claude")),
+            env_key,
+        };
+        assert_eq!(detected(true, true).provider(), "cli");
+        assert_eq!(detected(false, true).provider(), "api");
+        assert_eq!(detected(false, false).provider(), "cli");
+    }
+
+    #[test]
+    fn finds_programs_on_path() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("claude"), "").unwrap();
+        let path = std::env::join_paths(["/nonexistent", dir.path().to_str().unwrap()]).unwrap();
+        assert_eq!(
+            find_in_path("claude", &path),
+            Some(dir.path().join("claude"))
+        );
+        assert_eq!(find_in_path("missing", &path), None);
+    }
+
+    #[test]
+    fn global_content_omits_the_api_key() {
+        let content = global_content(&Config::default()).unwrap();
+        assert!(content.starts_with("version = "));
+        assert!(!content.contains("api_key ="));
+        assert!(content.contains("api_key_command = "));
+    }
+}
@@ -0,0 +1,213 @@
+use anyhow::{Context, Result, bail};
+use std::path::{Path, PathBuf};
+use toml_edit::{Decor, DocumentMut, Item, RawString, value};
+
+/// An upgrade step: rewrites a document one version up and describes each change
+pub(super) type Step = fn(&mut DocumentMut) -> Vec<String>;
+
+/// Upgrade steps; `STEPS[n]` turns a version `FIRST + n` file into version `FIRST + n + 1`.
+/// Add a step here whenever a release renames or restructures keys.
+const STEPS: &[Step] = &[];
+
+/// Version of the first versioned layout; files without a `version` key have it
+const FIRST: u32 = 1;
+
+/// Version written by this build of ccmt
+pub const CURRENT: u32 = FIRST + STEPS.len() as u32;
+
+/// Version a config file declares
+pub fn version_of(doc: &DocumentMut) -> u32 {
+    doc.get("version")
+        .and_then(|v| v.as_integer())
+        .map_or(FIRST, |v| v.max(0) as u32)
+}
+
+/// Apply the steps a document needs and describe them, without stamping the new version
+pub fn upgrade(doc: &mut DocumentMut) -> Result<Vec<String>> {
+    upgrade_with(doc, STEPS)
+}
+
+pub(super) fn upgrade_with(doc: &mut DocumentMut, steps: &[Step]) -> Result<Vec<String>> {
+    let version = version_of(doc);
+    let current = FIRST + steps.len() as u32;
+    if version > current {
+        bail!(
+            "config version {version} is newer than this ccmt supports ({current}); upgrade ccmt"
+        );
+    }
+    let pending = version.saturating_sub(FIRST) as usize;
+    Ok(steps[pending..].iter().flat_map(|step| step(doc)).collect())
+}
+
+/// Upgrade a config file in memory so older layouts keep loading. The content
+/// is returned as-is when no step changes it, so validation reports real lines.
+pub fn upgrade_str(content: &str) -> Result<String> {
+    let Ok(mut doc) = content.parse::<DocumentMut>() else {
+        // Leave syntax errors for validation to report
+        return Ok(content.to_string());
+    };
+    if upgrade(&mut doc)?.is_empty() {
+        return Ok(content.to_string());
+    }
+    Ok(doc.to_string())
+}
+
+/// `ccmt config migrate`: rewrite the file in the current layout, keeping a backup
+pub fn migrate_file(path: &Path) -> Result<()> {
+    if !path.is_file() {
+        bail!("No config file at {}", path.display());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config: {}", path.display()))?;
+    let mut doc: DocumentMut = content
+        .parse()
+        .with_context(|| format!("Failed to parse: {}", path.display()))?;
+    let from = version_of(&doc);
+    if from == CURRENT {
+        println!("{} is already at version {CURRENT}", path.display());
+        return Ok(());
+    }
+
+    let changes = upgrade(&mut doc)?;
+    stamp(&mut doc);
+
+    let backup = backup_path(path, from);
+    std::fs::copy(path, &backup)
+        .with_context(|| format!("Failed to back up config to {}", backup.display()))?;
+    std::fs::write(path, doc.to_string())?;
+
+    println!(
+        "Migrated {} from version {from} to {CURRENT}",
+        path.display()
+    );
+    for change in changes {
+        println!("  - {change}");
+    }
+    println!("Backup saved to {}", backup.display());
+    Ok(())
+}
+
+/// Set `version` as the first key of the document
+fn stamp(doc: &mut DocumentMut) {
+    // A comment heading the file belongs above `version`, not the first section
+    let header = match doc.iter_mut().next() {
+        Some((_, Item::Table(table))) => take_prefix(table.decor_mut()),
+        Some((mut key, _)) => take_prefix(key.leaf_decor_mut()),
+        None => None,
+    };
+    doc.remove("version");
+    // Root values print before every section, so this lands at the top
+    doc.insert("version", value(i64::from(CURRENT)));
+    if let Some(header) = header
+        && let Some(mut key) = doc.as_table_mut().key_mut("version")
+    {
+        key.leaf_decor_mut().set_prefix(header);
+    }
+}
+
+/// Split off the file's header comment, leaving a blank line before the first entry
+fn take_prefix(decor: &mut Decor) -> Option<RawString> {
+    let prefix = decor
+        .prefix()
+        .and_then(|p| p.as_str())
+        .unwrap_or_default()
+        .to_string();
+    let (header, rest) = match prefix.rfind("\n\n") {
+        Some(i) => (&prefix[..i + 1], &prefix[i + 1..]),
+        None => (prefix.as_str(), ""),
+    };
+    let (header, rest) = if header.trim().is_empty() {
+        (None, prefix.trim_start_matches('\n'))
+    } else {
+        (Some(header.into()), rest.trim_start_matches('\n'))
+    };
+    decor.set_prefix(format!("\n{rest}"));
+    header
+}
+
+fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".v{version}.bak"));
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test-only step: renames `commit.lang` to `commit.language`
+    fn rename_lang(doc: &mut DocumentMut) -> Vec<String> {
+        let Some(commit) = doc.get_mut("commit").and_then(|c| c.as_table_like_mut()) else {
+            return Vec::new();
+        };
+        let Some(lang) = commit.remove("lang") else {
+            return Vec::new();
+        };
+        commit.insert("language", lang);
+        vec!["renamed commit.lang to commit.language".to_string()]
+    }
+
+    #[test]
+    fn pending_steps_are_applied() {
+        let steps: &[Step] = &[rename_lang];
+        let mut doc: DocumentMut = "[commit]\nlang = \"ja\"\n".parse().unwrap();
+        assert_eq!(version_of(&doc), FIRST);
+        assert_eq!(
+            upgrade_with(&mut doc, steps).unwrap(),
+            vec!["renamed commit.lang to commit.language"]
+        );
+        assert_eq!(doc.to_string(), "[commit]\nlanguage = \"ja\"\n");
+
+        // A file already at the step's target version is left alone
+        let content = format!("version = {}\n[commit]\nlang = \"ja\"\n", FIRST + 1);
+        let mut doc: DocumentMut = content.parse().unwrap();
+        assert!(upgrade_with(&mut doc, steps).unwrap().is_empty());
+        assert_eq!(doc.to_string(), content);
+    }
+
+    #[test]
+    fn current_layouts_load_unchanged() {
+        let content = "# mine\n[commit]\nlanguage = \"ja\"\n";
+        assert_eq!(upgrade_str(content).unwrap(), content);
+        let content = format!("version = {CURRENT}\n[auth]\napi_key = \"\"\n");
+        assert_eq!(upgrade_str(&content).unwrap(), content);
+    }
+
+    #[test]
+    fn newer_versions_are_rejected() {
+        let mut doc: DocumentMut = format!("version = {}\n", CURRENT + 1).parse().unwrap();
+        assert!(upgrade(&mut doc).is_err());
+    }
+
+    #[test]
+    fn stamp_puts_version_first_and_keeps_comments() {
+        let mut doc: DocumentMut = "# My config\n[commit]\nlanguage = \"ja\"  # team\n"
+            .parse()
+            .unwrap();
+        stamp(&mut doc);
+        assert_eq!(
+            doc.to_string(),
+            format!("# My config\nversion = {CURRENT}\n\n[commit]\nlanguage = \"ja\"  # team\n")
+        );
+    }
+
+    #[test]
+    fn stamp_leaves_section_comments_in_place() {
+        let mut doc: DocumentMut = "# ccmt\n\n# Auth\n[auth]\nmodel = \"opus\"\n"
+            .parse()
+            .unwrap();
+        stamp(&mut doc);
+        assert_eq!(
+            doc.to_string(),
+            format!("# ccmt\nversion = {CURRENT}\n\n# Auth\n[auth]\nmodel = \"opus\"\n")
+        );
+    }
+
+    #[test]
+    fn backup_names_the_old_version() {
+        assert_eq!(
+            backup_path(Path::new("/x/.ccmt.toml"), 1),
+            PathBuf::from("/x/.ccmt.toml.v1.bak")
+        );
+    }
+}
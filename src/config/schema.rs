@@ -0,0 +1,105 @@
+use schemars::generate::SchemaSettings;
+use serde_json::{Value, json};
+
+use super::validate::{PROVIDERS, TICKET_POSITIONS};
+use super::{Config, migrate};
+
+/// JSON Schema for config files, generated from `Config` so it follows the Rust types
+pub fn schema() -> Value {
+    // Draft 7 is what taplo (Even Better TOML) understands best
+    let generator = SchemaSettings::draft07().into_generator();
+    let mut schema =
+        serde_json::to_value(generator.into_root_schema_for::<Config>()).unwrap_or_default();
+
+    // Unknown keys are errors when ccmt loads a file, so flag them in the editor too
+    schema["additionalProperties"] = json!(false);
+    for definition in schema["definitions"]
+        .as_object_mut()
+        .into_iter()
+        .flat_map(|d| d.values_mut())
+        .filter(|d| d["type"] == "object")
+    {
+        definition["additionalProperties"] = json!(false);
+    }
+    let definitions = &mut schema["definitions"];
+    definitions["AuthConfig"]["properties"]["provider"]["enum"] = json!(PROVIDERS);
+    definitions["CommitConfig"]["properties"]["ticket_position"]["enum"] = json!(TICKET_POSITIONS);
+    schema["properties"]["version"]["maximum"] = json!(migrate::CURRENT);
+
+    // Profiles are only a file-level concept, so they aren't part of `Config`
+    let sections: serde_json::Map<String, Value> = ["auth", "commit", "prompt"]
+        .into_iter()
+        .map(|s| (s.to_string(), schema["properties"][s].clone()))
+        .collect();
+    let mut profile = json!({
+        "type": "object",
+        "description": "Overrides applied when the profile is selected with --profile or CCMT_PROFILE, or its match rules fit the repository",
+        "properties": {
+            "match_remote": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Remote URL patterns, e.g. \"github.com/acme/*\""
+            },
+            "match_path": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Repository path patterns, e.g. \"~/work/*\""
+            }
+        },
+        "additionalProperties": false
+    });
+    if let Some(properties) = profile["properties"].as_object_mut() {
+        properties.extend(sections);
+    }
+    schema["properties"]["profiles"] = json!({
+        "type": "object",
+        "description": "Named config profiles",
+        "additionalProperties": profile
+    });
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schema_covers_every_key_with_its_default() {
+        let schema = schema();
+        let defaults = toml::Table::try_from(Config::default()).unwrap();
+        for (section, keys) in &defaults {
+            let Some(keys) = keys.as_table() else {
+                continue;
+            };
+            // Draft 7 wraps references that carry a default in allOf
+            let reference = schema["properties"][section]["allOf"][0]["$ref"]
+                .as_str()
+                .unwrap();
+            let name = reference.trim_start_matches("#/definitions/");
+            let properties = &schema["definitions"][name]["properties"];
+            for (key, value) in keys {
+                let property = &properties[key];
+                assert!(
+                    property["description"].is_string(),
+                    "{section}.{key} has no description"
+                );
+                assert_eq!(
+                    property["default"],
+                    serde_json::to_value(value).unwrap(),
+                    "{section}.{key}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn schema_describes_profiles() {
+        let schema = schema();
+        let profile = &schema["properties"]["profiles"]["additionalProperties"];
+        assert_eq!(profile["properties"]["match_remote"]["type"], "array");
+        assert_eq!(
+            profile["properties"]["commit"],
+            schema["properties"]["commit"]
+        );
+    }
+}
@@ -0,0 +1,527 @@
+use regex::Regex;
+use std::fmt;
+use toml_edit::{DocumentMut, ImDocument, TableLike};
+
+use super::{
+    Config, PartialAuthConfig, PartialCommitConfig, PartialConfig, PartialPromptConfig, migrate,
+};
+
+pub const PROVIDERS: &[&str] = &["cli", "api"];
+pub const MODEL_ALIASES: &[&str] = &["sonnet", "haiku", "opus"];
+/// Keys a `[profiles.<name>]` table has besides the config sections
+pub const PROFILE_RULES: &[&str] = &["match_remote", "match_path"];
+pub const TICKET_POSITIONS: &[&str] = &["none", "prefix", "scope", "footer"];
+
+#[derive(Debug, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem found in a config file
+#[derive(Debug)]
+pub struct Problem {
+    pub severity: Severity,
+    /// 1-based line in the file, when known
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        match self.line {
+            Some(line) => write!(f, "line {line}: {severity}: {}", self.message),
+            None => write!(f, "{severity}: {}", self.message),
+        }
+    }
+}
+
+/// Check a config file's contents: syntax, unknown keys, value types and values
+pub fn check(content: &str) -> Vec<Problem> {
+    check_with(content, migrate::upgrade)
+}
+
+/// Older layouts are checked the way they load: after the pending upgrade steps,
+/// with a reminder to run `ccmt config migrate` in place of unknown-key errors
+fn check_with(
+    content: &str,
+    upgrade: impl Fn(&mut DocumentMut) -> anyhow::Result<Vec<String>>,
+) -> Vec<Problem> {
+    let Ok(mut upgraded) = content.parse::<DocumentMut>() else {
+        // Syntax errors are reported against the file as written
+        return check_layout(content);
+    };
+    let line = ImDocument::parse(content)
+        .ok()
+        .and_then(|doc| key_line(content, &doc, &["version"]));
+    match upgrade(&mut upgraded) {
+        Err(e) => vec![Problem {
+            severity: Severity::Error,
+            line,
+            message: e.to_string(),
+        }],
+        Ok(changes) if changes.is_empty() => check_layout(content),
+        Ok(changes) => {
+            let mut problems = vec![Problem {
+                severity: Severity::Warning,
+                line,
+                message: format!(
+                    "old config layout ({}); run `ccmt config migrate`",
+                    changes.join(", ")
+                ),
+            }];
+            // Lines refer to the upgraded layout, so they are left out
+            problems.extend(
+                check_layout(&upgraded.to_string())
+                    .into_iter()
+                    .map(|p| Problem { line: None, ..p }),
+            );
+            problems
+        }
+    }
+}
+
+/// Check contents already in the current layout
+fn check_layout(content: &str) -> Vec<Problem> {
+    let doc = match ImDocument::parse(content) {
+        Ok(doc) => doc,
+        Err(e) => {
+            return vec![Problem {
+                severity: Severity::Error,
+                line: e.span().map(|s| line_at(content, s.start)),
+                message: e.message().trim().to_string(),
+            }];
+        }
+    };
+
+    // The known keys come from the serialized defaults, so they follow the Rust types
+    let known = toml::Table::try_from(Config::default()).unwrap_or_default();
+    let mut problems = Vec::new();
+    check_keys(content, doc.as_table(), &known, "", &mut problems);
+    if !problems.is_empty() {
+        return problems;
+    }
+
+    match toml::from_str::<PartialConfig>(content) {
+        Ok(partial) => {
+            let mut problems = check_values(
+                partial.auth.as_ref(),
+                partial.commit.as_ref(),
+                partial.prompt.as_ref(),
+                &|path| key_line(content, &doc, path),
+            );
+            for (name, profile) in partial.profiles.iter().flatten() {
+                problems.extend(check_values(
+                    profile.auth.as_ref(),
+                    profile.commit.as_ref(),
+                    profile.prompt.as_ref(),
+                    &|path| {
+                        let full: Vec<&str> = ["profiles", name.as_str()]
+                            .into_iter()
+                            .chain(path.iter().copied())
+                            .collect();
+                        key_line(content, &doc, &full)
+                    },
+                ));
+            }
+            problems
+        }
+        Err(e) => vec![Problem {
+            severity: Severity::Error,
+            line: e.span().map(|s| line_at(content, s.start)),
+            message: e.message().trim().to_string(),
+        }],
+    }
+}
+
+fn check_keys(
+    content: &str,
+    table: &dyn TableLike,
+    known: &toml::Table,
+    section: &str,
+    problems: &mut Vec<Problem>,
+) {
+    for (name, item) in table.iter() {
+        let line = table
+            .get_key_value(name)
+            .and_then(|(key, _)| key.span())
+            .map(|s| line_at(content, s.start));
+        let path = if section.is_empty() {
+            name.to_string()
+        } else {
+            format!("{section}.{name}")
+        };
+        // Each profile holds match rules plus the regular sections
+        if path == "profiles" {
+            let mut profile_known = known.clone();
+            profile_known.remove("version");
+            for rule in PROFILE_RULES {
+                profile_known.insert(rule.to_string(), toml::Value::Array(Vec::new()));
+            }
+            for (profile, profile_item) in item.as_table_like().into_iter().flat_map(|t| t.iter()) {
+                if let Some(profile_table) = profile_item.as_table_like() {
+                    let profile_path = format!("profiles.{profile}");
+                    check_keys(
+                        content,
+                        profile_table,
+                        &profile_known,
+                        &profile_path,
+                        problems,
+                    );
+                }
+            }
+            continue;
+        }
+        match known.get(name) {
+            // Empty by default means the keys are names the user picks, like commit.types
+            Some(toml::Value::Table(sub)) if sub.is_empty() => {}
+            Some(toml::Value::Table(sub)) => {
+                if let Some(sub_table) = item.as_table_like() {
+                    check_keys(content, sub_table, sub, &path, problems);
+                }
+            }
+            Some(_) => {}
+            None => {
+                let what = if section.is_empty() {
+                    format!("unknown section `{name}`")
+                } else {
+                    format!("unknown key `{name}` in [{section}]")
+                };
+                let message = match suggest(name, known.keys().map(String::as_str)) {
+                    Some(s) => format!("{what} (did you mean `{s}`?)"),
+                    None => what,
+                };
+                problems.push(Problem {
+                    severity: Severity::Error,
+                    line,
+                    message,
+                });
+            }
+        }
+    }
+}
+
+fn check_values(
+    auth: Option<&PartialAuthConfig>,
+    commit: Option<&PartialCommitConfig>,
+    prompt: Option<&PartialPromptConfig>,
+    line: &dyn Fn(&[&str]) -> Option<usize>,
+) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut error = |path: &[&str], message: String| {
+        problems.push(Problem {
+            severity: Severity::Error,
+            line: line(path),
+            message,
+        })
+    };
+
+    if let Some(auth) = auth {
+        if let Some(provider) = &auth.provider {
+            one_of(provider, PROVIDERS).unwrap_or_else(|m| error(&["auth", "provider"], m));
+        }
+        if let Some(model) = &auth.model
+            && !model.starts_with("claude-")
+        {
+            one_of(model, MODEL_ALIASES).unwrap_or_else(|m| {
+                error(
+                    &["auth", "model"],
+                    format!("{m}, or a full model ID like `claude-sonnet-4-5`"),
+                )
+            });
+        }
+    }
+
+    if let Some(commit) = commit {
+        if let Some(position) = &commit.ticket_position {
+            one_of(position, TICKET_POSITIONS)
+                .unwrap_or_else(|m| error(&["commit", "ticket_position"], m));
+        }
+        for pattern in commit.ticket_patterns.iter().flatten() {
+            if let Err(e) = Regex::new(pattern) {
+                let first = e.to_string().lines().last().unwrap_or_default().to_string();
+                error(
+                    &["commit", "ticket_patterns"],
+                    format!("invalid regex `{pattern}`: {}", first.trim()),
+                );
+            }
+        }
+        for name in commit.types.iter().flat_map(|t| t.keys()) {
+            if !crate::format::is_header_token(name) {
+                error(
+                    &["commit", "types", name],
+                    format!(
+                        "`{name}` can't be used as a commit type; use letters, digits, `-`, `_`, `.` or `/`"
+                    ),
+                );
+            }
+        }
+        for scope in commit.scopes.iter().flat_map(|s| s.allowed()) {
+            if !crate::format::is_header_token(scope) {
+                error(
+                    &["commit", "scopes"],
+                    format!(
+                        "`{scope}` can't be used as a scope; use letters, digits, `-`, `_`, `.` or `/`"
+                    ),
+                );
+            }
+        }
+        if commit.max_subject_length == Some(0) {
+            error(
+                &["commit", "max_subject_length"],
+                "must be greater than 0".to_string(),
+            );
+        }
+    }
+
+    if let Some(prompt) = prompt
+        && prompt.max_diff_length == Some(0)
+    {
+        error(
+            &["prompt", "max_diff_length"],
+            "must be greater than 0".to_string(),
+        );
+    }
+
+    // Any language name works in the prompt, but codes are what the rest of ccmt expects
+    if let Some(language) = commit.and_then(|c| c.language.as_ref())
+        && !is_language_code(language)
+    {
+        problems.push(Problem {
+            severity: Severity::Warning,
+            line: line(&["commit", "language"]),
+            message: format!(
+                "`{language}` is not a language code like `en`, `ja` or `pt-BR`; it is passed to the model as-is"
+            ),
+        });
+    }
+
+    problems
+}
+
+/// Error message for a value outside `allowed`, with a suggestion when one is close
+fn one_of(value: &str, allowed: &[&str]) -> Result<(), String> {
+    if allowed.contains(&value) {
+        return Ok(());
+    }
+    let expected = allowed
+        .iter()
+        .map(|v| format!("`{v}`"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Err(match suggest(value, allowed.iter().copied()) {
+        Some(s) => {
+            format!("unknown value `{value}` (did you mean `{s}`?); expected one of {expected}")
+        }
+        None => format!("unknown value `{value}`; expected one of {expected}"),
+    })
+}
+
+/// Closest candidate within a small edit distance
+pub fn suggest<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    candidates
+        .map(|c| (strsim::damerau_levenshtein(name, c), c))
+        .filter(|(d, c)| *d <= (c.len() / 3).max(1))
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
+
+fn is_language_code(value: &str) -> bool {
+    Regex::new(r"^[a-z]{2,3}(-[A-Za-z0-9]{2,8})*$").is_ok_and(|re| re.is_match(value))
+}
+
+fn line_at(content: &str, offset: usize) -> usize {
+    content[..offset.min(content.len())].matches('\n').count() + 1
+}
+
+/// Line of a key such as `["commit", "language"]`
+fn key_line<S: AsRef<str>>(content: &str, doc: &ImDocument<S>, path: &[&str]) -> Option<usize> {
+    let (last, sections) = path.split_last()?;
+    let mut table: &dyn TableLike = doc.as_table();
+    for section in sections {
+        table = table.get(section)?.as_table_like()?;
+    }
+    let (key, _) = table.get_key_value(last)?;
+    key.span().map(|s| line_at(content, s.start))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(content: &str) -> Vec<String> {
+        check(content).iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn valid_config_has_no_problems() {
+        let content =
+            "[auth]\nprovider = \"api\"\nmodel = \"haiku\"\n\n[commit]\nlanguage = \"ja\"\n";
+        assert!(check(content).is_empty());
+        assert!(check(&toml::to_string_pretty(&Config::default()).unwrap()).is_empty());
+    }
+
+    #[test]
+    fn unknown_keys_suggest_the_closest_name() {
+        let content =
+            "[commit]\nemoji = true\nlanguge = \"ja\"\n\n[prompt]\nmax_diff_lenght = 100\n";
+        assert_eq!(
+            messages(content),
+            vec![
+                "line 3: error: unknown key `languge` in [commit] (did you mean `language`?)",
+                "line 6: error: unknown key `max_diff_lenght` in [prompt] (did you mean `max_diff_length`?)",
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_sections_and_dotted_keys() {
+        assert_eq!(
+            messages("[comit]\nemoji = true\n"),
+            vec!["line 1: error: unknown section `comit` (did you mean `commit`?)"]
+        );
+        assert_eq!(
+            messages("commit.wrap = 72\n"),
+            vec!["line 1: error: unknown key `wrap` in [commit]"]
+        );
+    }
+
+    #[test]
+    fn type_errors_report_the_line() {
+        let problems = check("[commit]\n\nemoji = \"yes\"\n");
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, Some(3));
+        assert_eq!(problems[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn commit_types_and_scopes() {
+        let content = "[commit]\nscopes = \"infer\"\n\n[commit.types]\ndeps = { description = \"Dependency updates\", emoji = \"⬆️\" }\ni18n = { description = \"Translations\" }\n";
+        assert!(check(content).is_empty());
+        assert_eq!(
+            messages(
+                "[commit]\nscopes = [\"api\", \"my scope\"]\n\n[commit.types]\n\"new feat\" = { description = \"x\" }\n"
+            ),
+            vec![
+                "line 5: error: `new feat` can't be used as a commit type; use letters, digits, `-`, `_`, `.` or `/`",
+                "line 2: error: `my scope` can't be used as a scope; use letters, digits, `-`, `_`, `.` or `/`",
+            ]
+        );
+        let problems = check("[commit.types]\ndeps = { desc = \"x\" }\n");
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn accepted_types_parse_as_headers() {
+        let content = "[commit]\nscopes = [\"api/v2\"]\n\n[commit.types]\nsecurity-fix = { description = \"x\" }\n\"ops.infra\" = { description = \"x\" }\n";
+        assert!(check(content).is_empty());
+        for kind in ["security-fix", "ops.infra"] {
+            let subject = format!("{kind}(api/v2): patch it");
+            let header = crate::format::parse_header(&subject).unwrap();
+            assert_eq!(header.kind, kind);
+            assert_eq!(header.scope, Some("api/v2"));
+        }
+    }
+
+    #[test]
+    fn future_versions_are_reported() {
+        assert!(check(&format!("version = {}\n", migrate::CURRENT)).is_empty());
+        assert!(check("[auth]\napi_key = \"\"\n").is_empty());
+        let problems = check("version = 99\n");
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, Some(1));
+        assert_eq!(problems[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn old_layouts_are_checked_after_upgrading() {
+        fn rename_lang(doc: &mut DocumentMut) -> Vec<String> {
+            let Some(commit) = doc.get_mut("commit").and_then(|c| c.as_table_like_mut()) else {
+                return Vec::new();
+            };
+            let Some(lang) = commit.remove("lang") else {
+                return Vec::new();
+            };
+            commit.insert("language", lang);
+            vec!["renamed commit.lang to commit.language".to_string()]
+        }
+        let steps: &[migrate::Step] = &[rename_lang];
+        let check_old = |content: &str| -> Vec<String> {
+            check_with(content, |doc| migrate::upgrade_with(doc, steps))
+                .iter()
+                .map(|p| p.to_string())
+                .collect()
+        };
+
+        assert_eq!(
+            check_old("[commit]\nlang = \"ja\"\n"),
+            vec![
+                "warning: old config layout (renamed commit.lang to commit.language); run `ccmt config migrate`"
+            ]
+        );
+        // Keys that are unknown in every layout are still errors
+        assert_eq!(
+            check_old("[commit]\nlang = \"ja\"\nemojj = true\n"),
+            vec![
+                "warning: old config layout (renamed commit.lang to commit.language); run `ccmt config migrate`",
+                "error: unknown key `emojj` in [commit] (did you mean `emoji`?)",
+            ]
+        );
+    }
+
+    #[test]
+    fn syntax_errors_report_the_line() {
+        let problems = check("[commit]\nemoji = \n");
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, Some(2));
+    }
+
+    #[test]
+    fn values_are_validated() {
+        let content = "[auth]\nprovider = \"apii\"\nmodel = \"sonet\"\n\n[commit]\nticket_position = \"top\"\nticket_patterns = [\"(\"]\nlanguage = \"Japanese\"\n";
+        let problems = messages(content);
+        assert_eq!(problems.len(), 5);
+        assert_eq!(
+            problems[0],
+            "line 2: error: unknown value `apii` (did you mean `api`?); expected one of `cli`, `api`"
+        );
+        assert!(
+            problems[1]
+                .starts_with("line 3: error: unknown value `sonet` (did you mean `sonnet`?)")
+        );
+        assert!(problems[2].starts_with("line 6: error: unknown value `top`"));
+        assert!(problems[3].starts_with("line 7: error: invalid regex `(`"));
+        assert!(problems[4].starts_with("line 8: warning:"));
+    }
+
+    #[test]
+    fn profiles_are_checked_like_the_main_sections() {
+        let content = "[profiles.work]\nmatch_remote = [\"github.com/acme/*\"]\n\n[profiles.work.commit]\nlanguge = \"ja\"\n\n[profiles.oss.auth]\nprovider = \"apii\"\n";
+        let problems = messages(content);
+        assert_eq!(
+            problems,
+            vec![
+                "line 5: error: unknown key `languge` in [profiles.work.commit] (did you mean `language`?)"
+            ]
+        );
+        let content = "[profiles.oss]\nmatch_path = [\"~/oss\"]\n\n[profiles.oss.auth]\nprovider = \"apii\"\n";
+        assert!(messages(content)[0].starts_with("line 5: error: unknown value `apii`"));
+        assert!(
+            messages("[profiles.oss]\nmatch_remotes = []\n")[0].contains(
+                "unknown key `match_remotes` in [profiles.oss] (did you mean `match_remote`?)"
+            )
+        );
+    }
+
+    #[test]
+    fn full_model_ids_and_regional_languages_are_accepted() {
+        let content =
+            "[auth]\nmodel = \"claude-sonnet-4-5-20250929\"\n\n[commit]\nlanguage = \"pt-BR\"\n";
+        assert!(check(content).is_empty());
+    }
+}
@@ -1,5 +1,393 @@
+use crate::config::{CommitType, Config};
+use serde::Serialize;
+
+/// Conventional Commits types used when commit.types is empty: name, description, Gitmoji
+pub const DEFAULT_TYPES: &[(&str, &str, &str)] = &[
+    ("feat", "A new feature", "✨"),
+    ("fix", "A bug fix", "🐛"),
+    ("docs", "Documentation only changes", "📝"),
+    ("style", "Formatting that does not change behavior", "💄"),
+    (
+        "refactor",
+        "A change that neither fixes a bug nor adds a feature",
+        "♻️",
+    ),
+    ("perf", "A performance improvement", "⚡"),
+    ("test", "Adding or fixing tests", "✅"),
+    ("build", "Build system or dependency changes", "📦"),
+    ("ci", "CI configuration changes", "👷"),
+    (
+        "chore",
+        "Other changes that don't modify source or tests",
+        "🔧",
+    ),
+    ("revert", "Reverts a previous commit", "⏪"),
+];
+
+/// Message rules shared by generation (prompt, `format_message`) and `ccmt lint`
+#[derive(Debug, Clone)]
+pub struct Rules {
+    pub conventional: bool,
+    /// Allowed types with their descriptions, in prompt order
+    pub types: Vec<(String, CommitType)>,
+    /// Allowed scopes; empty allows any
+    pub scopes: Vec<String>,
+    pub max_subject_length: usize,
+    pub body_wrap: usize,
+    /// Trailer keys every message must carry
+    pub required_trailers: Vec<String>,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self::from_config(&Config::default())
+    }
+}
+
+impl Rules {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            conventional: config.commit.conventional,
+            types: if config.commit.types.is_empty() {
+                DEFAULT_TYPES
+                    .iter()
+                    .map(|(name, description, emoji)| {
+                        let kind = CommitType {
+                            description: description.to_string(),
+                            emoji: emoji.to_string(),
+                        };
+                        (name.to_string(), kind)
+                    })
+                    .collect()
+            } else {
+                config
+                    .commit
+                    .types
+                    .iter()
+                    .map(|(name, kind)| (name.clone(), kind.clone()))
+                    .collect()
+            },
+            scopes: config.commit.scopes.allowed().to_vec(),
+            max_subject_length: config.commit.max_subject_length,
+            body_wrap: config.commit.body_wrap,
+            required_trailers: config.commit.required_trailers.clone(),
+        }
+    }
+
+    pub fn type_names(&self) -> Vec<&str> {
+        self.types.iter().map(|(name, _)| name.as_str()).collect()
+    }
+}
+
+/// A single rule violation found by `lint`
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Violation {
+    pub rule: &'static str,
+    /// 1-based line number in the message
+    pub line: usize,
+    pub message: String,
+}
+
+/// Parsed Conventional Commits header: `<type>(<scope>)!: <description>`
+#[derive(Debug, PartialEq)]
+pub struct Header<'a> {
+    pub kind: &'a str,
+    pub scope: Option<&'a str>,
+    pub breaking: bool,
+    pub description: &'a str,
+}
+
+impl Header<'_> {
+    pub fn render(&self) -> String {
+        let scope = self.scope.map(|s| format!("({s})")).unwrap_or_default();
+        let bang = if self.breaking { "!" } else { "" };
+        format!("{}{scope}{bang}: {}", self.kind, self.description)
+    }
+}
+
+/// Parse a subject line as a Conventional Commits header
+pub fn parse_header(subject: &str) -> Option<Header<'_>> {
+    let (head, description) = subject.split_once(": ")?;
+    let (head, breaking) = match head.strip_suffix('!') {
+        Some(h) => (h, true),
+        None => (head, false),
+    };
+    let (kind, scope) = match head.split_once('(') {
+        Some((k, rest)) => (k, Some(rest.strip_suffix(')')?)),
+        None => (head, None),
+    };
+    if !is_header_token(kind) {
+        return None;
+    }
+    Some(Header {
+        kind,
+        scope,
+        breaking,
+        description: description.trim(),
+    })
+}
+
+/// Whether a type or scope fits in a `<type>(<scope>)!:` header
+pub fn is_header_token(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'))
+}
+
+/// Parse a `Key: value` trailer line
+pub fn parse_trailer(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once(": ")?;
+    let valid_key = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    (valid_key && !value.trim().is_empty()).then(|| (key, value.trim()))
+}
+
+fn is_trailer_line(line: &str) -> bool {
+    parse_trailer(line).is_some()
+}
+
+/// Split a message into (content, trailer block). The trailer block is the
+/// last paragraph when every line in it is a `Key: value` trailer.
+pub fn split_trailers(msg: &str) -> (&str, &str) {
+    let msg = msg.trim_end();
+    let Some(idx) = msg.rfind("\n\n") else {
+        return (msg, "");
+    };
+    let last = &msg[idx + 2..];
+    if !last.is_empty() && last.lines().all(is_trailer_line) {
+        (msg[..idx].trim_end(), last)
+    } else {
+        (msg, "")
+    }
+}
+
+/// Append a `key: value` trailer, joining an existing trailer block if present
+pub fn append_trailer(msg: &str, key: &str, value: &str) -> String {
+    let line = format!("{key}: {value}");
+    let (content, trailers) = split_trailers(msg);
+    if trailers.lines().any(|l| l == line) {
+        return msg.trim_end().to_string();
+    }
+    if trailers.is_empty() {
+        format!("{content}\n\n{line}")
+    } else {
+        format!("{content}\n\n{trailers}\n{line}")
+    }
+}
+
+/// Comment character for an editor buffer holding `message`
+///
+/// Like git's `core.commentChar=auto`: keeps `preferred` unless a line of the message
+/// starts with it (e.g. a `#42 fix login` subject), then takes the first free candidate.
+pub fn comment_char_for(message: &str, preferred: char) -> char {
+    std::iter::once(preferred)
+        .chain("#;@!$%^&|:".chars())
+        .find(|c| !message.lines().any(|l| l.trim_start().starts_with(*c)))
+        .unwrap_or(preferred)
+}
+
+/// Drop lines starting with the comment character and trim the result
+pub fn strip_comments(text: &str, comment_char: char) -> String {
+    text.lines()
+        .filter(|l| !l.starts_with(comment_char))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Drop a leading Gitmoji token (e.g. "✨ feat: x" -> "feat: x")
+pub fn strip_gitmoji(subject: &str) -> &str {
+    match subject.split_once(' ') {
+        Some((first, rest)) if !first.chars().any(|c| c.is_ascii_alphanumeric()) => rest,
+        _ => subject,
+    }
+}
+
+/// Messages git or tooling generates, which are exempt from conventional checks
+fn is_exempt_subject(subject: &str) -> bool {
+    ["Merge ", "Revert \"", "fixup! ", "squash! ", "amend! "]
+        .iter()
+        .any(|p| subject.starts_with(p))
+}
+
+fn char_len(s: &str) -> usize {
+    s.chars().count()
+}
+
+/// Whether a body line is too long and can be broken at a space.
+/// Indented code and single long tokens such as URLs are left alone.
+fn needs_wrap(line: &str, width: usize) -> bool {
+    if char_len(line) <= width || line.starts_with("    ") || line.starts_with('\t') {
+        return false;
+    }
+    let (_, content) = split_list_marker(line);
+    content.trim().contains(' ')
+}
+
+/// Split a line into its indent + list marker ("  - ", "1. ") and the content
+fn split_list_marker(line: &str) -> (&str, &str) {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+    let marker = if trimmed.starts_with("- ") || trimmed.starts_with("* ") {
+        2
+    } else {
+        let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+        if digits > 0 && trimmed[digits..].starts_with(". ") {
+            digits + 2
+        } else {
+            0
+        }
+    };
+    line.split_at(indent + marker)
+}
+
+/// Greedy word wrap with a hanging indent for list items
+fn wrap_line(line: &str, width: usize) -> Vec<String> {
+    if !needs_wrap(line, width) {
+        return vec![line.to_string()];
+    }
+    let (prefix, content) = split_list_marker(line);
+    let hanging = " ".repeat(char_len(prefix));
+    let mut lines = Vec::new();
+    let mut current = prefix.to_string();
+    let mut empty = true;
+    for word in content.split_whitespace() {
+        if !empty && char_len(&current) + 1 + char_len(word) > width {
+            lines.push(current);
+            current = hanging.clone();
+            empty = true;
+        }
+        if !empty {
+            current.push(' ');
+        }
+        current.push_str(word);
+        empty = false;
+    }
+    lines.push(current);
+    lines
+}
+
+/// Violations of the configured type and scope lists
+///
+/// Formatting can't fix these in a generated message, so they are checked before review.
+pub fn vocabulary_violations(msg: &str, rules: &Rules) -> Vec<Violation> {
+    lint(msg, rules)
+        .into_iter()
+        .filter(|v| matches!(v.rule, "type-enum" | "scope-enum"))
+        .collect()
+}
+
+/// Check a commit message against the rules
+pub fn lint(msg: &str, rules: &Rules) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let mut add = |rule, line, message: String| {
+        violations.push(Violation {
+            rule,
+            line,
+            message,
+        })
+    };
+
+    let lines: Vec<&str> = msg.trim_end().lines().collect();
+    let subject = lines.first().copied().unwrap_or("");
+    if subject.trim().is_empty() {
+        add("subject-empty", 1, "Subject line is empty".to_string());
+        return violations;
+    }
+
+    let len = char_len(subject);
+    if len > rules.max_subject_length {
+        add(
+            "subject-length",
+            1,
+            format!(
+                "Subject is {len} characters; the limit is {}",
+                rules.max_subject_length
+            ),
+        );
+    }
+
+    if rules.conventional && !is_exempt_subject(subject) {
+        match parse_header(strip_gitmoji(subject)) {
+            None => add(
+                "header-format",
+                1,
+                "Subject is not in Conventional Commits format: <type>(<scope>): <description>"
+                    .to_string(),
+            ),
+            Some(h) => {
+                if !rules.types.iter().any(|(t, _)| t == h.kind) {
+                    add(
+                        "type-enum",
+                        1,
+                        format!(
+                            "Type '{}' is not one of: {}",
+                            h.kind,
+                            rules.type_names().join(", ")
+                        ),
+                    );
+                }
+                if let Some(scope) = h.scope
+                    && !rules.scopes.is_empty()
+                    && !rules.scopes.iter().any(|s| s == scope)
+                {
+                    add(
+                        "scope-enum",
+                        1,
+                        format!("Scope '{scope}' is not one of: {}", rules.scopes.join(", ")),
+                    );
+                }
+                if h.description.is_empty() {
+                    add("subject-empty", 1, "Description is empty".to_string());
+                }
+            }
+        }
+    }
+
+    if lines.len() > 1 && !lines[1].trim().is_empty() {
+        add(
+            "blank-line",
+            2,
+            "Subject must be followed by a blank line".to_string(),
+        );
+    }
+
+    let (content, trailers) = split_trailers(msg);
+    for (i, line) in content.lines().enumerate().skip(1) {
+        if needs_wrap(line, rules.body_wrap) {
+            add(
+                "body-line-length",
+                i + 1,
+                format!(
+                    "Body line is {} characters; wrap at {}",
+                    char_len(line),
+                    rules.body_wrap
+                ),
+            );
+        }
+    }
+
+    let trailer_line = content.lines().count() + 2;
+    for key in &rules.required_trailers {
+        let present = trailers
+            .lines()
+            .filter_map(parse_trailer)
+            .any(|(k, _)| k.eq_ignore_ascii_case(key));
+        if !present {
+            add(
+                "trailer-missing",
+                trailer_line,
+                format!("Missing required trailer '{key}:'"),
+            );
+        }
+    }
+
+    violations
+}
+
 /// Clean up the AI-generated commit message
-pub fn format_message(raw: &str) -> String {
+pub fn format_message(raw: &str, rules: &Rules) -> String {
     let mut msg = raw.trim().to_string();
 
     // Remove surrounding quotes
@@ -26,13 +414,21 @@ pub fn format_message(raw: &str) -> String {
         }
     }
 
-    // Ensure title line is not too long (72 chars max for first line)
+    // Keep any trailer block intact while reflowing the rest
+    let (content, trailers) = split_trailers(&msg);
+    let trailers = trailers.to_string();
+    msg = content.to_string();
+
+    // Ensure title line is not too long
     let lines: Vec<&str> = msg.lines().collect();
     if let Some(first) = lines.first()
-        && first.len() > 72
+        && char_len(first) > rules.max_subject_length
     {
         // Try to break at a word boundary
-        let truncated: String = first.chars().take(69).collect();
+        let truncated: String = first
+            .chars()
+            .take(rules.max_subject_length.saturating_sub(3))
+            .collect();
         if let Some(last_space) = truncated.rfind(' ') {
             let title = &first[..last_space];
             let rest = first[last_space..].trim();
@@ -51,60 +447,273 @@ pub fn format_message(raw: &str) -> String {
         }
     }
 
-    msg.trim().to_string()
+    // Wrap body lines to the configured width
+    let msg = msg
+        .trim()
+        .lines()
+        .enumerate()
+        .flat_map(|(i, line)| {
+            if i == 0 {
+                vec![line.to_string()]
+            } else {
+                wrap_line(line, rules.body_wrap)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if trailers.is_empty() {
+        msg.to_string()
+    } else {
+        format!("{msg}\n\n{trailers}")
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::Scopes;
 
     #[test]
     fn clean_message() {
-        assert_eq!(format_message("feat: add login"), "feat: add login");
+        assert_eq!(
+            format_message("feat: add login", &Rules::default()),
+            "feat: add login"
+        );
     }
 
     #[test]
     fn strips_surrounding_quotes() {
-        assert_eq!(format_message("\"feat: add login\""), "feat: add login");
-        assert_eq!(format_message("'fix: typo'"), "fix: typo");
+        assert_eq!(
+            format_message("\"feat: add login\"", &Rules::default()),
+            "feat: add login"
+        );
+        assert_eq!(
+            format_message("'fix: typo'", &Rules::default()),
+            "fix: typo"
+        );
     }
 
     #[test]
     fn strips_code_block() {
         let input = "```\nfeat: add login\n```";
-        assert_eq!(format_message(input), "feat: add login");
+        assert_eq!(format_message(input, &Rules::default()), "feat: add login");
     }
 
     #[test]
     fn strips_code_block_with_lang() {
         let input = "```text\nfeat: add login\n```";
-        assert_eq!(format_message(input), "feat: add login");
+        assert_eq!(format_message(input, &Rules::default()), "feat: add login");
     }
 
     #[test]
     fn strips_commit_message_prefix() {
         assert_eq!(
-            format_message("Commit message: feat: add login"),
+            format_message("Commit message: feat: add login", &Rules::default()),
             "feat: add login"
         );
-        assert_eq!(format_message("commit: fix: typo"), "fix: typo");
+        assert_eq!(
+            format_message("commit: fix: typo", &Rules::default()),
+            "fix: typo"
+        );
     }
 
     #[test]
     fn preserves_multiline() {
         let input = "feat: add login\n\nImplement JWT auth";
-        assert_eq!(format_message(input), input);
+        assert_eq!(format_message(input, &Rules::default()), input);
     }
 
     #[test]
     fn trims_whitespace() {
-        assert_eq!(format_message("  feat: add login  \n"), "feat: add login");
+        assert_eq!(
+            format_message("  feat: add login  \n", &Rules::default()),
+            "feat: add login"
+        );
+    }
+
+    #[test]
+    fn parses_conventional_header() {
+        let h = parse_header("feat(auth)!: add login").unwrap();
+        assert_eq!(h.kind, "feat");
+        assert_eq!(h.scope, Some("auth"));
+        assert!(h.breaking);
+        assert_eq!(h.description, "add login");
+        assert_eq!(h.render(), "feat(auth)!: add login");
+    }
+
+    #[test]
+    fn rejects_non_conventional_header() {
+        assert!(parse_header("Add login page").is_none());
+        assert!(parse_header("fix some thing: details").is_none());
+        assert!(parse_header("feat(auth: broken").is_none());
+    }
+
+    #[test]
+    fn appends_trailer_to_existing_block() {
+        let msg = "feat: add login\n\nBody text\n\nRefs: PROJ-1";
+        assert_eq!(
+            append_trailer(msg, "Signed-off-by", "A <a@b.c>"),
+            "feat: add login\n\nBody text\n\nRefs: PROJ-1\nSigned-off-by: A <a@b.c>"
+        );
+    }
+
+    #[test]
+    fn appends_trailer_as_new_paragraph() {
+        assert_eq!(
+            append_trailer("feat: add login", "Refs", "PROJ-1"),
+            "feat: add login\n\nRefs: PROJ-1"
+        );
+        // Subject alone is never treated as a trailer block
+        assert_eq!(split_trailers("feat: add login"), ("feat: add login", ""));
+    }
+
+    #[test]
+    fn wrapping_keeps_trailer_block_last() {
+        let input = "feat: this is a very long commit message title that definitely exceeds the seventy two character limit\n\nSigned-off-by: A <a@b.c>";
+        let result = format_message(input, &Rules::default());
+        assert!(result.lines().next().unwrap().len() <= 72);
+        assert!(result.ends_with("\n\nSigned-off-by: A <a@b.c>"));
+    }
+
+    #[test]
+    fn parses_trailer_lines() {
+        assert_eq!(
+            parse_trailer("Co-authored-by: A <a@b.c>"),
+            Some(("Co-authored-by", "A <a@b.c>"))
+        );
+        assert_eq!(parse_trailer("Not a trailer: x"), None);
+        assert_eq!(parse_trailer("Refs:"), None);
+    }
+
+    #[test]
+    fn strips_comment_lines() {
+        let text = "feat: add login\n# comment\n\nBody\n; other\n";
+        assert_eq!(
+            strip_comments(text, '#'),
+            "feat: add login\n\nBody\n; other"
+        );
+        assert_eq!(
+            strip_comments(text, ';'),
+            "feat: add login\n# comment\n\nBody"
+        );
+    }
+
+    #[test]
+    fn comment_char_avoids_the_message() {
+        assert_eq!(comment_char_for("feat: add login\n\nBody", '#'), '#');
+        assert_eq!(comment_char_for("#42 fix login\n\nBody", '#'), ';');
+        assert_eq!(comment_char_for("#42 fix\n; note", '#'), '@');
+        assert_eq!(comment_char_for("#42 fix", ';'), ';');
+
+        let c = comment_char_for("#42 fix login", '#');
+        let buffer = format!("#42 fix login\n\n{c} Edit the commit message above.\n");
+        assert_eq!(strip_comments(&buffer, c), "#42 fix login");
+    }
+
+    #[test]
+    fn wraps_long_body_lines() {
+        let input = "feat: add login\n\nThis body line is deliberately much longer than the seventy-two character limit.\n\n- a bullet item that is also far too long to fit on a single seventy-two column line\n\nSee https://example.com/a/very/long/url/that/cannot/be/broken/anywhere/at/all/really";
+        let result = format_message(input, &Rules::default());
+        assert!(
+            result
+                .lines()
+                .all(|l| l.chars().count() <= 72 || !l.contains(' '))
+        );
+        assert!(result.contains("\n  column line"));
+        assert!(lint(&result, &Rules::default()).is_empty());
+    }
+
+    #[test]
+    fn lint_accepts_valid_message() {
+        let msg = "feat(auth): add login\n\nImplement JWT auth.\n\nSigned-off-by: A <a@b.c>";
+        assert!(lint(msg, &Rules::default()).is_empty());
+    }
+
+    #[test]
+    fn configured_types_replace_the_defaults() {
+        let mut config = Config::default();
+        let kind = CommitType {
+            description: "Dependency updates".to_string(),
+            emoji: String::new(),
+        };
+        config.commit.types.insert("deps".to_string(), kind);
+        let rules = Rules::from_config(&config);
+        assert_eq!(rules.type_names(), ["deps"]);
+        assert!(lint("deps: bump serde", &rules).is_empty());
+        let rules_hit: Vec<&str> = lint("feat: add x", &rules).iter().map(|v| v.rule).collect();
+        assert_eq!(rules_hit, vec!["type-enum"]);
+    }
+
+    #[test]
+    fn generated_messages_are_checked_against_types_and_scopes() {
+        let mut config = Config::default();
+        for name in ["deps", "security"] {
+            let kind = CommitType {
+                description: format!("{name} changes"),
+                emoji: String::new(),
+            };
+            config.commit.types.insert(name.to_string(), kind);
+        }
+        config.commit.scopes = Scopes::List(vec!["api".to_string()]);
+        let rules = Rules::from_config(&config);
+
+        let generated = format_message("feat(ui): add a dark mode toggle", &rules);
+        let hit: Vec<&str> = vocabulary_violations(&generated, &rules)
+            .iter()
+            .map(|v| v.rule)
+            .collect();
+        assert_eq!(hit, vec!["type-enum", "scope-enum"]);
+        assert!(vocabulary_violations("deps(api): bump serde", &rules).is_empty());
+        // Other rules are left to lint
+        let long = format!("deps: {}", "x".repeat(100));
+        assert!(vocabulary_violations(&long, &rules).is_empty());
+    }
+
+    #[test]
+    fn lint_reports_each_rule() {
+        let rules = Rules {
+            scopes: vec!["api".to_string()],
+            required_trailers: vec!["Signed-off-by".to_string()],
+            ..Rules::default()
+        };
+        let long_body = "word ".repeat(20);
+        let msg = format!(
+            "feature(ui): {}\nno blank line\n{long_body}",
+            "x".repeat(70)
+        );
+        let rules_hit: Vec<&str> = lint(&msg, &rules).iter().map(|v| v.rule).collect();
+        assert_eq!(
+            rules_hit,
+            vec![
+                "subject-length",
+                "type-enum",
+                "scope-enum",
+                "blank-line",
+                "body-line-length",
+                "trailer-missing",
+            ]
+        );
+    }
+
+    #[test]
+    fn lint_header_format_and_exemptions() {
+        let rules = Rules::default();
+        let v = lint("Add login page", &rules);
+        assert_eq!(v[0].rule, "header-format");
+        assert!(lint("Merge branch 'main' into feature", &rules).is_empty());
+        assert!(lint("Revert \"feat: add login\"", &rules).is_empty());
+        assert!(lint("✨ feat: add login", &rules).is_empty());
+        let relaxed = Rules {
+            conventional: false,
+            ..Rules::default()
+        };
+        assert!(lint("Add login page", &relaxed).is_empty());
     }
 
     #[test]
     fn wraps_long_title() {
         let input = "feat: this is a very long commit message title that definitely exceeds the seventy two character limit for git";
-        let result = format_message(input);
+        let result = format_message(input, &Rules::default());
         let first_line = result.lines().next().unwrap();
         assert!(first_line.len() <= 72);
     }
@@ -1,6 +1,29 @@
 use anyhow::{Context, Result, bail};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+/// A multi-step operation that the next commit will conclude
+#[derive(Debug, PartialEq)]
+pub enum InProgress {
+    Merge(String),
+    Revert(String),
+    CherryPick(String),
+    Rebase,
+    Squash,
+}
+
+/// Extra options forwarded to `git commit`
+#[derive(Debug, Default)]
+pub struct CommitOptions {
+    /// `Some(None)` signs with the default key, `Some(Some(id))` with a specific key
+    pub gpg_sign: Option<Option<String>>,
+    pub no_verify: bool,
+    pub author: Option<String>,
+    pub date: Option<String>,
+    pub extra_args: Vec<String>,
+}
+
 fn run_git(args: &[&str]) -> Result<String> {
     let output = Command::new("git")
         .args(args)
@@ -23,10 +46,267 @@ pub fn git_diff_staged() -> Result<String> {
     run_git(&["diff", "--cached"])
 }
 
+/// Staged diff against HEAD's parent, i.e. the full content of an amended commit
+pub fn git_diff_amend() -> Result<String> {
+    // The empty tree stands in for the parent of a root commit
+    const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+    let parent = git_rev_parse("HEAD~1").unwrap_or_else(|_| EMPTY_TREE.to_string());
+    run_git(&["diff", "--cached", &parent])
+}
+
 pub fn git_diff_all() -> Result<String> {
     run_git(&["diff"])
 }
 
+/// Value of a git config key, or `None` when unset
+pub fn git_config(key: &str) -> Option<String> {
+    run_git(&["config", "--get", key])
+        .ok()
+        .filter(|v| !v.is_empty())
+}
+
+/// Path-typed value from the global git config, with `~` expanded
+pub fn git_config_global_path(key: &str) -> Option<PathBuf> {
+    run_git(&["config", "--global", "--type=path", "--get", key])
+        .ok()
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+pub fn git_config_set_global(key: &str, value: &str) -> Result<()> {
+    run_git(&["config", "--global", key, value]).map(|_| ())
+}
+
+pub fn git_config_unset_global(key: &str) -> Result<()> {
+    run_git(&["config", "--global", "--unset", key]).map(|_| ())
+}
+
+/// Comment character from core.commentChar ("auto" and unset fall back to '#')
+pub fn git_comment_char() -> char {
+    git_config("core.commentChar")
+        .filter(|v| v != "auto")
+        .and_then(|v| v.chars().next())
+        .unwrap_or('#')
+}
+
+pub fn git_toplevel() -> Result<PathBuf> {
+    run_git(&["rev-parse", "--show-toplevel"]).map(PathBuf::from)
+}
+
+/// URLs of all configured remotes
+pub fn git_remote_urls() -> Vec<String> {
+    run_git(&["config", "--get-regexp", r"^remote\..*\.url$"])
+        .map(|out| {
+            out.lines()
+                .filter_map(|l| l.split_once(' ').map(|(_, url)| url.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Default branch of origin (from origin/HEAD), falling back to main or master
+pub fn git_default_branch() -> String {
+    if let Ok(head) = run_git(&["symbolic-ref", "--short", "refs/remotes/origin/HEAD"]) {
+        return head;
+    }
+    ["main", "master"]
+        .into_iter()
+        .find(|b| git_rev_parse(b).is_ok())
+        .unwrap_or("main")
+        .to_string()
+}
+
+pub fn git_merge_base(a: &str, b: &str) -> Result<String> {
+    run_git(&["merge-base", a, b])
+}
+
+/// Contents of the file configured as commit.template, if any
+pub fn git_commit_template() -> Option<String> {
+    let path = PathBuf::from(run_git(&["config", "--path", "--get", "commit.template"]).ok()?);
+    let path = if path.is_relative() {
+        git_toplevel().ok()?.join(path)
+    } else {
+        path
+    };
+    std::fs::read_to_string(path).ok()
+}
+
+/// Editor git would use for commit messages
+pub fn git_editor() -> Result<String> {
+    run_git(&["var", "GIT_EDITOR"])
+}
+
+fn git_path(name: &str) -> Result<PathBuf> {
+    run_git(&["rev-parse", "--git-path", name]).map(PathBuf::from)
+}
+
+/// Read a state file from the git directory (e.g. MERGE_MSG)
+pub fn git_state_file(name: &str) -> Option<String> {
+    std::fs::read_to_string(git_path(name).ok()?).ok()
+}
+
+fn state_head(name: &str) -> Option<String> {
+    git_state_file(name)?
+        .lines()
+        .next()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+}
+
+/// Detect an in-progress merge, revert, cherry-pick or rebase
+pub fn git_in_progress() -> Option<InProgress> {
+    if let Some(head) = state_head("MERGE_HEAD") {
+        return Some(InProgress::Merge(head));
+    }
+    if let Some(head) = state_head("REVERT_HEAD") {
+        return Some(InProgress::Revert(head));
+    }
+    if let Some(head) = state_head("CHERRY_PICK_HEAD") {
+        return Some(InProgress::CherryPick(head));
+    }
+    let rebasing = ["rebase-merge", "rebase-apply"]
+        .iter()
+        .any(|d| git_path(d).is_ok_and(|p| p.is_dir()));
+    if rebasing {
+        return Some(InProgress::Rebase);
+    }
+    // Left behind by `git merge --squash` until the next commit
+    git_path("SQUASH_MSG")
+        .is_ok_and(|p| p.is_file())
+        .then_some(InProgress::Squash)
+}
+
+/// Subjects of non-merge commits in `range`, oldest first
+pub fn git_log_subjects(range: &str) -> Result<Vec<String>> {
+    let out = run_git(&["log", "--no-merges", "--reverse", "--format=%s", range])?;
+    Ok(out.lines().map(str::to_string).collect())
+}
+
+/// Full message of a single commit
+pub fn git_commit_message(rev: &str) -> Result<String> {
+    run_git(&["log", "-1", "--format=%B", rev])
+}
+
+/// Files listed under the "Conflicts:" comment that git writes into MERGE_MSG
+pub fn parse_merge_conflicts(merge_msg: &str, comment_char: char) -> Vec<String> {
+    let mut in_conflicts = false;
+    let mut files = Vec::new();
+    for line in merge_msg.lines() {
+        let Some(comment) = line.strip_prefix(comment_char) else {
+            in_conflicts = false;
+            continue;
+        };
+        if comment.trim() == "Conflicts:" {
+            in_conflicts = true;
+        } else if in_conflicts && comment.starts_with('\t') {
+            files.push(comment.trim().to_string());
+        } else if in_conflicts && !comment.trim().is_empty() {
+            in_conflicts = false;
+        }
+    }
+    files
+}
+
+/// Current branch name (works on unborn branches; errors when HEAD is detached)
+pub fn git_current_branch() -> Result<String> {
+    run_git(&["symbolic-ref", "--short", "HEAD"])
+}
+
+/// Configured identity as `Name <email>`
+pub fn git_user_ident() -> Result<String> {
+    let name = run_git(&["config", "user.name"])?;
+    let email = run_git(&["config", "user.email"])?;
+    Ok(format!("{name} <{email}>"))
+}
+
+pub fn git_staged_files() -> Result<Vec<String>> {
+    let out = run_git(&["diff", "--cached", "--name-only"])?;
+    Ok(out.lines().map(str::to_string).collect())
+}
+
+/// Messages of the last `count` non-merge commits, newest first.
+/// When `paths` is non-empty, only commits touching those paths are considered.
+pub fn git_recent_messages(count: usize, paths: &[String]) -> Result<Vec<String>> {
+    let limit = format!("-n{count}");
+    let mut args = vec!["log", "--no-merges", "--format=%B%x1e", limit.as_str()];
+    if !paths.is_empty() {
+        args.push("--");
+        args.extend(paths.iter().map(String::as_str));
+    }
+    Ok(split_messages(&run_git(&args)?))
+}
+
+/// Full messages of all commits in `range`, oldest first
+pub fn git_log_messages(range: &str) -> Result<Vec<String>> {
+    Ok(split_messages(&run_git(&[
+        "log",
+        "--reverse",
+        "--format=%B%x1e",
+        range,
+    ])?))
+}
+
+/// (hash, message) of non-merge commits in `range`, newest first
+pub fn git_log_commits(range: &str) -> Result<Vec<(String, String)>> {
+    let out = run_git(&["log", "--no-merges", "--format=%H%x1f%B%x1e", range])?;
+    Ok(split_messages(&out)
+        .into_iter()
+        .filter_map(|c| {
+            let (hash, msg) = c.split_once('\x1f')?;
+            Some((hash.to_string(), msg.trim().to_string()))
+        })
+        .collect())
+}
+
+/// Committer date of `rev` as YYYY-MM-DD
+pub fn git_commit_date(rev: &str) -> Result<String> {
+    run_git(&["log", "-1", "--format=%cs", rev])
+}
+
+/// Tags reachable from HEAD
+pub fn git_tags_merged() -> Result<Vec<String>> {
+    let out = run_git(&["tag", "--merged", "HEAD"])?;
+    Ok(out.lines().map(str::to_string).collect())
+}
+
+/// Create an annotated tag, optionally GPG-signed (`Some(None)` uses the default key)
+pub fn git_tag_annotated(name: &str, msg: &str, sign: Option<Option<&str>>) -> Result<String> {
+    let mut file = tempfile::NamedTempFile::new().context("Failed to create message file")?;
+    file.write_all(msg.as_bytes())?;
+    file.flush()?;
+
+    let path = file.path().display().to_string();
+    let mut args = vec!["tag".to_string(), "-a".to_string(), name.to_string()];
+    args.extend(["-F".to_string(), path]);
+    match sign {
+        Some(Some(key)) => args.push(format!("--local-user={key}")),
+        Some(None) => args.push("--sign".to_string()),
+        None => {}
+    }
+    run_git(&args.iter().map(String::as_str).collect::<Vec<_>>())
+}
+
+fn split_messages(out: &str) -> Vec<String> {
+    out.split('\x1e')
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Resolve a revision to a full commit hash
+pub fn git_rev_parse(rev: &str) -> Result<String> {
+    run_git(&["rev-parse", "--verify", &format!("{rev}^{{commit}}")])
+}
+
+pub fn git_diff_range(from: &str, to: &str) -> Result<String> {
+    run_git(&["diff", from, to])
+}
+
+pub fn git_reset_soft(rev: &str) -> Result<String> {
+    run_git(&["reset", "--soft", rev])
+}
+
 pub fn git_status() -> Result<String> {
     run_git(&["status", "--porcelain"])
 }
@@ -35,10 +315,159 @@ pub fn git_stage_all() -> Result<String> {
     run_git(&["add", "."])
 }
 
-pub fn git_commit(msg: &str) -> Result<String> {
-    run_git(&["commit", "-m", msg])
+/// Path of a hook as git resolves it (honors core.hooksPath)
+pub fn git_hook_path(name: &str) -> Result<PathBuf> {
+    git_path(&format!("hooks/{name}"))
+}
+
+/// Hook location inside the git directory, ignoring core.hooksPath
+pub fn git_default_hook_path(name: &str) -> Result<PathBuf> {
+    run_git(&["rev-parse", "--git-common-dir"])
+        .map(|dir| PathBuf::from(dir).join("hooks").join(name))
+}
+
+/// Name of a hook that exited non-zero, from GIT_TRACE2_EVENT output
+fn failed_hook(events: &str) -> Option<String> {
+    let mut hooks = std::collections::HashMap::new();
+    for event in events
+        .lines()
+        .filter_map(|l| serde_json::from_str::<serde_json::Value>(l).ok())
+    {
+        let id = (event["sid"].to_string(), event["child_id"].to_string());
+        match event["event"].as_str() {
+            Some("child_start") if event["child_class"] == "hook" => {
+                hooks.insert(id, event["hook_name"].as_str().unwrap_or("git").to_string());
+            }
+            Some("child_exit") if event["code"] != 0 => {
+                if let Some(name) = hooks.remove(&id) {
+                    return Some(name);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn commit_args(msg_file: &Path, opts: &CommitOptions) -> Vec<String> {
+    let mut args = vec!["commit".to_string(), "-F".to_string()];
+    args.push(msg_file.display().to_string());
+    match &opts.gpg_sign {
+        Some(Some(key)) => args.push(format!("--gpg-sign={key}")),
+        Some(None) => args.push("--gpg-sign".to_string()),
+        None => {}
+    }
+    if opts.no_verify {
+        args.push("--no-verify".to_string());
+    }
+    if let Some(author) = &opts.author {
+        args.push(format!("--author={author}"));
+    }
+    if let Some(date) = &opts.date {
+        args.push(format!("--date={date}"));
+    }
+    args.extend(opts.extra_args.iter().cloned());
+    args
+}
+
+/// Commit with the message written to a temp file, so any content is passed verbatim
+pub fn git_commit(msg: &str, opts: &CommitOptions) -> Result<String> {
+    let mut file = tempfile::NamedTempFile::new().context("Failed to create message file")?;
+    file.write_all(msg.as_bytes())?;
+    file.flush()?;
+
+    let args = commit_args(file.path(), opts);
+    // git's trace2 events record which hooks ran and how they exited
+    let trace = tempfile::NamedTempFile::new().context("Failed to create trace file")?;
+    let output = Command::new("git")
+        .args(&args)
+        .env("GIT_TRACE2_EVENT", trace.path())
+        .output()
+        .context("Failed to execute git")?;
+
+    if output.status.success() {
+        return Ok(String::from_utf8_lossy(&output.stdout).trim().to_string());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let detail = [stderr.as_str(), stdout.as_str()]
+        .iter()
+        .filter(|s| !s.is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let events = std::fs::read_to_string(trace.path()).unwrap_or_default();
+    if let Some(hook) = failed_hook(&events) {
+        bail!(
+            "git commit was rejected by the {hook} hook. Fix the reported issues or re-run with --no-verify.\n{detail}"
+        );
+    }
+    bail!("git commit failed: {detail}")
 }
 
 pub fn git_push() -> Result<String> {
     run_git(&["push"])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_merge_conflicts() {
+        let msg = "Merge branch 'feature'\n\n# Conflicts:\n#\tsrc/a.rs\n#\tsrc/b.rs\n#\n# It looks like you may be committing a merge.\n";
+        assert_eq!(
+            parse_merge_conflicts(msg, '#'),
+            vec!["src/a.rs", "src/b.rs"]
+        );
+        assert!(parse_merge_conflicts("Merge branch 'feature'", '#').is_empty());
+    }
+
+    #[test]
+    fn failed_hook_comes_from_trace_events() {
+        let start = r#"{"event":"child_start","sid":"s1","child_id":0,"child_class":"hook","hook_name":"commit-msg"}"#;
+        let gpg =
+            r#"{"event":"child_start","sid":"s1","child_id":1,"child_class":"?","argv":["gpg"]}"#;
+        let exit = |id: u32, code: i32| {
+            format!(r#"{{"event":"child_exit","sid":"s1","child_id":{id},"code":{code}}}"#)
+        };
+        let rejected = [start, &exit(0, 1)].join("\n");
+        assert_eq!(failed_hook(&rejected).as_deref(), Some("commit-msg"));
+        let passed = [start, &exit(0, 0), gpg, &exit(1, 2)].join("\n");
+        assert_eq!(failed_hook(&passed), None);
+        assert_eq!(failed_hook(""), None);
+    }
+
+    #[test]
+    fn commit_args_default() {
+        let args = commit_args(Path::new("/tmp/msg"), &CommitOptions::default());
+        assert_eq!(args, vec!["commit", "-F", "/tmp/msg"]);
+    }
+
+    #[test]
+    fn commit_args_pass_through() {
+        let opts = CommitOptions {
+            gpg_sign: Some(Some("ABCD".to_string())),
+            no_verify: true,
+            author: Some("A <a@b.c>".to_string()),
+            date: Some("2024-01-01".to_string()),
+            extra_args: vec!["--allow-empty".to_string()],
+        };
+        let args = commit_args(Path::new("/tmp/msg"), &opts);
+        assert_eq!(
+            args,
+            vec![
+                "commit",
+                "-F",
+                "/tmp/msg",
+                "--gpg-sign=ABCD",
+                "--no-verify",
+                "--author=A <a@b.c>",
+                "--date=2024-01-01",
+                "--allow-empty",
+            ]
+        );
+    }
+}
@@ -0,0 +1,358 @@
+use anyhow::{Result, bail};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::hook_command;
+
+/// Hook managers that own the repository's hooks; ccmt installs as an entry in their config
+#[derive(Debug, PartialEq)]
+pub enum Framework {
+    /// `.husky/` directory with one script per hook
+    Husky(PathBuf),
+    /// `lefthook.yml` (or one of its variants)
+    Lefthook(PathBuf),
+    /// `.pre-commit-config.yaml`
+    PreCommit(PathBuf),
+}
+
+const LEFTHOOK_FILES: &[&str] = &[
+    "lefthook.yml",
+    "lefthook.yaml",
+    ".lefthook.yml",
+    ".lefthook.yaml",
+];
+
+/// Find a hook manager configured at the repository root
+pub fn detect(root: &Path) -> Option<Framework> {
+    let husky = root.join(".husky");
+    if husky.is_dir() {
+        return Some(Framework::Husky(husky));
+    }
+    if let Some(file) = LEFTHOOK_FILES
+        .iter()
+        .map(|f| root.join(f))
+        .find(|p| p.is_file())
+    {
+        return Some(Framework::Lefthook(file));
+    }
+    let pre_commit = root.join(".pre-commit-config.yaml");
+    if pre_commit.is_file() {
+        return Some(Framework::PreCommit(pre_commit));
+    }
+    None
+}
+
+impl Framework {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Framework::Husky(_) => "husky",
+            Framework::Lefthook(_) => "lefthook",
+            Framework::PreCommit(_) => "pre-commit",
+        }
+    }
+
+    /// Whether the manager's config already runs ccmt for this hook
+    pub fn has_entry(&self, kind: &str) -> bool {
+        let file = match self {
+            Framework::Husky(dir) => dir.join(kind),
+            Framework::Lefthook(path) | Framework::PreCommit(path) => path.clone(),
+        };
+        fs::read_to_string(file).is_ok_and(|c| c.contains(&format!("ccmt hook run {kind}")))
+    }
+
+    pub fn install(&self, kind: &str, mode: &str) -> Result<()> {
+        let command = hook_command(kind, mode);
+        match self {
+            Framework::Husky(dir) => {
+                let path = dir.join(kind);
+                let content = fs::read_to_string(&path).unwrap_or_default();
+                let updated = husky_add(&content, kind, &command);
+                fs::write(&path, updated)?;
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+                }
+                println!("Added ccmt to husky hook {}", path.display());
+            }
+            Framework::Lefthook(path) => {
+                let content = fs::read_to_string(path)?;
+                let Some(updated) = lefthook_add(&content, kind, &command) else {
+                    bail!(
+                        "{} already configures {kind}; add this command to it:\n\n{}",
+                        path.display(),
+                        lefthook_block(kind, &command).trim()
+                    );
+                };
+                fs::write(path, updated)?;
+                println!("Added ccmt to {}", path.display());
+                println!("Run `lefthook install` if the {kind} hook is not set up yet");
+            }
+            Framework::PreCommit(path) => {
+                let content = fs::read_to_string(path)?;
+                let Some(updated) = pre_commit_add(&content, kind, &command) else {
+                    bail!(
+                        "Could not add ccmt to {}; add this entry under `repos:`:\n\n{}",
+                        path.display(),
+                        pre_commit_block(kind, &command, "  ").trim_end()
+                    );
+                };
+                fs::write(path, updated)?;
+                println!("Added ccmt to {}", path.display());
+                println!("Run `pre-commit install --hook-type {kind}` to enable it");
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove ccmt's entry; returns false when there was none
+    pub fn remove(&self, kind: &str) -> Result<bool> {
+        match self {
+            Framework::Husky(dir) => {
+                let path = dir.join(kind);
+                let Ok(content) = fs::read_to_string(&path) else {
+                    return Ok(false);
+                };
+                let Some(updated) = husky_remove(&content, kind) else {
+                    return Ok(false);
+                };
+                if updated.trim().is_empty() {
+                    fs::remove_file(&path)?;
+                } else {
+                    fs::write(&path, updated)?;
+                }
+                println!("Removed ccmt from husky hook {}", path.display());
+            }
+            Framework::Lefthook(path) | Framework::PreCommit(path) => {
+                let content = fs::read_to_string(path)?;
+                let updated = match self {
+                    Framework::Lefthook(_) => remove_block(&content, |c| lefthook_block(kind, c)),
+                    _ => pre_commit_remove(&content, kind),
+                };
+                let Some(updated) = updated else {
+                    if content.contains(&format!("ccmt hook run {kind}")) {
+                        bail!(
+                            "The ccmt entry in {} was edited; remove it manually",
+                            path.display()
+                        );
+                    }
+                    return Ok(false);
+                };
+                fs::write(path, updated)?;
+                println!("Removed ccmt from {}", path.display());
+            }
+        }
+        Ok(true)
+    }
+}
+
+fn husky_add(content: &str, kind: &str, command: &str) -> String {
+    if content.contains(&format!("ccmt hook run {kind}")) {
+        return content.to_string();
+    }
+    let mut out = content.to_string();
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    // Teammates without ccmt installed can still commit
+    out.push_str(&format!(
+        "if command -v ccmt >/dev/null 2>&1; then {command} \"$@\"; fi\n"
+    ));
+    out
+}
+
+fn husky_remove(content: &str, kind: &str) -> Option<String> {
+    let marker = format!("ccmt hook run {kind}");
+    if !content.contains(&marker) {
+        return None;
+    }
+    let kept: Vec<&str> = content.lines().filter(|l| !l.contains(&marker)).collect();
+    Some(kept.iter().map(|l| format!("{l}\n")).collect())
+}
+
+fn lefthook_block(kind: &str, command: &str) -> String {
+    // lefthook substitutes {1}, {2}, ... with the hook's arguments
+    let args = match kind {
+        "prepare-commit-msg" => "{1} {2} {3}",
+        _ => "{1}",
+    };
+    format!(
+        "\n{kind}:\n  commands:\n    ccmt:\n      run: {} {args}\n",
+        guarded(command)
+    )
+}
+
+/// Append the hook section, unless the file already has one (it can't be merged without a YAML parser)
+fn lefthook_add(content: &str, kind: &str, command: &str) -> Option<String> {
+    if content.lines().any(|l| l.trim_end() == format!("{kind}:")) {
+        return None;
+    }
+    let mut out = content.trim_end().to_string();
+    out.push('\n');
+    out.push_str(&lefthook_block(kind, command));
+    Some(out)
+}
+
+fn pre_commit_block(kind: &str, command: &str, indent: &str) -> String {
+    // pre-commit passes the message file as the filename argument
+    let lines = [
+        "- repo: local".to_string(),
+        "  hooks:".to_string(),
+        format!("    - id: ccmt-{kind}"),
+        "      name: ccmt".to_string(),
+        format!("      entry: {}", guarded(command)),
+        "      language: system".to_string(),
+        format!("      stages: [{kind}]"),
+        "      always_run: true".to_string(),
+    ];
+    lines.iter().map(|l| format!("{indent}{l}\n")).collect()
+}
+
+/// Append a local repo entry when `repos:` is the last top-level key
+fn pre_commit_add(content: &str, kind: &str, command: &str) -> Option<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    let repos = lines.iter().position(|l| l.trim_end() == "repos:")?;
+    let top_level_after = lines[repos + 1..]
+        .iter()
+        .any(|l| !l.is_empty() && !l.starts_with([' ', '#', '-']) && !l.starts_with("---"));
+    if top_level_after {
+        return None;
+    }
+    let indent = lines[repos + 1..]
+        .iter()
+        .find(|l| l.trim_start().starts_with("- "))
+        .map(|l| &l[..l.len() - l.trim_start().len()])
+        .unwrap_or("  ");
+    let mut out = content.trim_end().to_string();
+    out.push('\n');
+    out.push_str(&pre_commit_block(kind, command, indent));
+    Some(out)
+}
+
+fn pre_commit_remove(content: &str, kind: &str) -> Option<String> {
+    ["", "  ", "    "]
+        .into_iter()
+        .find_map(|indent| remove_block(content, |c| pre_commit_block(kind, c, indent)))
+}
+
+/// `command` wrapped so it passes when ccmt isn't installed; arguments follow as "$@"
+///
+/// Framework configs are committed, so teammates without ccmt run them too.
+fn guarded(command: &str) -> String {
+    format!("sh -c 'command -v ccmt >/dev/null 2>&1 || exit 0; {command} \"$@\"' ccmt")
+}
+
+/// Remove a block ccmt wrote, trying both commit-msg modes
+fn remove_block(content: &str, block: impl Fn(&str) -> String) -> Option<String> {
+    let kind_commands = [
+        "ccmt hook run prepare-commit-msg",
+        "ccmt hook run commit-msg",
+        "ccmt hook run commit-msg --mode repair",
+    ];
+    kind_commands.into_iter().find_map(|command| {
+        let block = block(command);
+        let normalized = format!("{}\n", content.trim_end());
+        normalized
+            .contains(&block)
+            .then(|| normalized.replacen(&block, "", 1))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn husky_appends_once_and_removes() {
+        let original = ". \"$(dirname -- \"$0\")/_/husky.sh\"\nnpx lint-staged\n";
+        let added = husky_add(original, "commit-msg", "ccmt hook run commit-msg");
+        assert_eq!(
+            added,
+            format!(
+                "{original}if command -v ccmt >/dev/null 2>&1; then ccmt hook run commit-msg \"$@\"; fi\n"
+            )
+        );
+        assert_eq!(
+            husky_add(&added, "commit-msg", "ccmt hook run commit-msg"),
+            added
+        );
+        assert_eq!(husky_remove(&added, "commit-msg").unwrap(), original);
+        assert!(husky_remove(original, "commit-msg").is_none());
+    }
+
+    #[test]
+    fn lefthook_appends_section() {
+        let original = "pre-commit:\n  commands:\n    fmt:\n      run: cargo fmt --check\n";
+        let added = lefthook_add(
+            original,
+            "prepare-commit-msg",
+            "ccmt hook run prepare-commit-msg",
+        )
+        .unwrap();
+        assert!(added.starts_with(original));
+        assert!(added.ends_with(
+            "\nprepare-commit-msg:\n  commands:\n    ccmt:\n      run: sh -c 'command -v ccmt >/dev/null 2>&1 || exit 0; ccmt hook run prepare-commit-msg \"$@\"' ccmt {1} {2} {3}\n"
+        ));
+        let removed = remove_block(&added, |c| lefthook_block("prepare-commit-msg", c));
+        assert_eq!(removed.unwrap(), original);
+    }
+
+    #[test]
+    fn lefthook_refuses_existing_section() {
+        let original = "commit-msg:\n  commands:\n    check:\n      run: check {1}\n";
+        assert!(lefthook_add(original, "commit-msg", "ccmt hook run commit-msg").is_none());
+    }
+
+    #[test]
+    fn pre_commit_appends_with_matching_indent() {
+        let original = "repos:\n- repo: https://github.com/pre-commit/pre-commit-hooks\n  rev: v4.0.0\n  hooks:\n  - id: trailing-whitespace\n";
+        let added = pre_commit_add(
+            original,
+            "commit-msg",
+            "ccmt hook run commit-msg --mode repair",
+        )
+        .unwrap();
+        assert!(added.contains(
+            "\n- repo: local\n  hooks:\n    - id: ccmt-commit-msg\n      name: ccmt\n      entry: sh -c 'command -v ccmt >/dev/null 2>&1 || exit 0; ccmt hook run commit-msg --mode repair \"$@\"' ccmt\n"
+        ));
+        assert!(added.contains("      stages: [commit-msg]\n"));
+        assert_eq!(pre_commit_remove(&added, "commit-msg").unwrap(), original);
+    }
+
+    #[test]
+    fn guarded_command_passes_without_ccmt() {
+        let run = |path: &str| {
+            std::process::Command::new("sh")
+                .arg("-c")
+                .arg(format!("{} msg-file", guarded("ccmt hook run commit-msg")))
+                .env("PATH", path)
+                .status()
+                .unwrap()
+        };
+        let empty = tempfile::tempdir().unwrap();
+        assert!(run(&format!("{}:/bin:/usr/bin", empty.path().display())).success());
+
+        // With ccmt on PATH, its exit status and the arguments come through
+        let bin = tempfile::tempdir().unwrap();
+        let ccmt = bin.path().join("ccmt");
+        fs::write(
+            &ccmt,
+            "#!/bin/sh\n[ \"$4\" = msg-file ] || exit 3\nexit 1\n",
+        )
+        .unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&ccmt, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        let status = run(&format!("{}:/bin:/usr/bin", bin.path().display()));
+        assert_eq!(status.code(), Some(1));
+    }
+
+    #[test]
+    fn pre_commit_requires_repos_last() {
+        let original = "repos:\n  - repo: local\n    hooks: []\nci:\n  autofix: true\n";
+        assert!(pre_commit_add(original, "commit-msg", "ccmt hook run commit-msg").is_none());
+        assert!(pre_commit_add("ci: {}\n", "commit-msg", "ccmt hook run commit-msg").is_none());
+    }
+}
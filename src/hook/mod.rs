@@ -0,0 +1,651 @@
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::git;
+
+mod framework;
+
+const HOOK_MARKER: &str = "# Installed by ccmt - AI commit message generator";
+
+/// Written into hook scripts so outdated installs can be detected
+const VERSION_PREFIX: &str = "# ccmt-hook-version: ";
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const HOOK_KINDS: [&str; 2] = ["prepare-commit-msg", "commit-msg"];
+
+/// Marks the pass-through hooks written next to ccmt's in its global hooks directory
+const SHIM_MARKER: &str = "# Installed by ccmt - runs the repository's own hook";
+
+/// Hooks git looks up in core.hooksPath (githooks(5)); fsmonitor-watchman is only run when configured
+const STANDARD_HOOKS: &[&str] = &[
+    "applypatch-msg",
+    "pre-applypatch",
+    "post-applypatch",
+    "pre-commit",
+    "pre-merge-commit",
+    "prepare-commit-msg",
+    "commit-msg",
+    "post-commit",
+    "pre-rebase",
+    "post-checkout",
+    "post-merge",
+    "pre-push",
+    "pre-receive",
+    "update",
+    "proc-receive",
+    "post-receive",
+    "post-update",
+    "reference-transaction",
+    "push-to-checkout",
+    "pre-auto-gc",
+    "post-rewrite",
+    "sendemail-validate",
+    "p4-changelist",
+    "p4-prepare-changelist",
+    "p4-post-changelist",
+    "p4-pre-submit",
+    "post-index-change",
+];
+
+/// Suffix an existing hook is renamed to when ccmt chains it
+const CHAINED_SUFFIX: &str = ".ccmt-chained";
+
+/// What the prepare-commit-msg hook should do for a commit
+#[derive(Debug, PartialEq)]
+pub enum HookPlan {
+    Skip,
+    Generate {
+        /// Describe the whole amended commit instead of the staged changes
+        amend: bool,
+        /// The message file holds a template to fill in
+        template: bool,
+    },
+}
+
+/// Decide based on the commit source git passes as the hook's second argument
+pub fn plan(source: Option<&str>, amend_requested: bool) -> HookPlan {
+    let generate = |amend, template| HookPlan::Generate { amend, template };
+    match source.unwrap_or("") {
+        "" | "merge" | "squash" => generate(false, false),
+        "template" => generate(false, true),
+        // Amend, -c or -C: keep the existing message unless asked (CCMT_AMEND=1)
+        "commit" if amend_requested => generate(true, false),
+        // -m / -F: the user already wrote the message
+        _ => HookPlan::Skip,
+    }
+}
+
+/// New message file contents: the generated message followed by git's comment lines
+pub fn compose(message: &str, original: &str, comment_char: char) -> String {
+    let comments: Vec<&str> = original
+        .lines()
+        .filter(|l| l.starts_with(comment_char))
+        .collect();
+    if comments.is_empty() {
+        format!("{message}\n")
+    } else {
+        format!("{message}\n\n{}\n", comments.join("\n"))
+    }
+}
+
+/// Command that runs the hook logic, as written into hook scripts and framework configs
+pub fn hook_command(kind: &str, mode: &str) -> String {
+    if kind == "commit-msg" && mode == "repair" {
+        format!("ccmt hook run {kind} --mode repair")
+    } else {
+        format!("ccmt hook run {kind}")
+    }
+}
+
+/// Hook script: a thin shim around `ccmt hook run` that also runs the hooks it chains
+///
+/// `chained` holds shell expressions for hook paths; hooks installed by ccmt are skipped
+/// so a repository hook never runs ccmt twice.
+fn hook_script(kind: &str, mode: &str, chain: &str, chained: &[String]) -> String {
+    let purpose = match kind {
+        "commit-msg" => format!("Checks the commit message with ccmt lint (mode: {mode})"),
+        _ => "Generates the commit message".to_string(),
+    };
+    let run_chained: String = chained
+        .iter()
+        .map(|hook| {
+            format!(
+                "if [ -x {hook} ] && ! grep -qF \"{HOOK_MARKER}\" {hook}; then\n    {hook} \"$@\" || exit $?\nfi\n"
+            )
+        })
+        .collect();
+    let mut script = format!(
+        "#!/bin/sh\n{HOOK_MARKER}\n{VERSION_PREFIX}{VERSION}\n# {purpose}; logic lives in `ccmt hook run`\n\n"
+    );
+    if chain == "before" && !run_chained.is_empty() {
+        script.push_str(&run_chained);
+        script.push('\n');
+    }
+    script.push_str(&format!(
+        "if command -v ccmt >/dev/null 2>&1; then\n    {} \"$@\" || exit $?\nfi\n",
+        hook_command(kind, mode)
+    ));
+    if chain == "after" && !run_chained.is_empty() {
+        script.push('\n');
+        script.push_str(&run_chained);
+    }
+    script
+}
+
+/// Pass-through hook: hands `name` to the repository's own hook, if it has one
+fn shim_script(name: &str) -> String {
+    format!(
+        "#!/bin/sh\n{SHIM_MARKER}\n# core.hooksPath points here for ccmt; keep running {name} from the repository\n\n\
+         hook=\"$(git rev-parse --git-common-dir)/hooks/{name}\"\n\
+         if [ -x \"$hook\" ]; then\n    exec \"$hook\" \"$@\"\nfi\n"
+    )
+}
+
+/// Version recorded in a hook script (None for scripts older than the marker)
+fn script_version(content: &str) -> Option<&str> {
+    content
+        .lines()
+        .find_map(|l| l.strip_prefix(VERSION_PREFIX))
+        .map(str::trim)
+}
+
+fn chained_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(CHAINED_SUFFIX);
+    path.with_file_name(name)
+}
+
+fn is_ccmt_hook(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|c| c.contains(HOOK_MARKER))
+}
+
+fn is_shim(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|c| c.contains(SHIM_MARKER))
+}
+
+/// Fill `dir` with pass-through hooks for every hook kind that has no script there yet
+///
+/// Without them, pointing core.hooksPath at `dir` would switch off each repository's
+/// pre-commit, pre-push, post-checkout, ... hooks.
+fn write_shims(dir: &Path) -> Result<usize> {
+    let mut written = 0;
+    for name in STANDARD_HOOKS {
+        let path = dir.join(name);
+        if path.exists() {
+            continue;
+        }
+        fs::write(&path, shim_script(name))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        }
+        written += 1;
+    }
+    Ok(written)
+}
+
+/// Whether `dir` holds nothing but pass-through hooks
+fn only_shims(dir: &Path) -> bool {
+    fs::read_dir(dir).is_ok_and(|entries| entries.flatten().all(|e| is_shim(&e.path())))
+}
+
+/// Directory for hooks ccmt manages globally (`~/.config/ccmt/hooks`)
+fn global_hooks_dir() -> Result<PathBuf> {
+    let config_dir = dirs::config_dir().context("Could not determine config directory")?;
+    Ok(config_dir.join("ccmt").join("hooks"))
+}
+
+/// Template directory used when installing through init.templateDir
+fn template_dir() -> Result<PathBuf> {
+    match git::git_config_global_path("init.templateDir") {
+        Some(dir) => Ok(dir),
+        None => {
+            let config_dir = dirs::config_dir().context("Could not determine config directory")?;
+            Ok(config_dir.join("ccmt").join("template"))
+        }
+    }
+}
+
+/// Write the hook script at `path`, chaining whatever hook was there
+fn write_hook(path: &Path, kind: &str, mode: &str, chain: &str, repo_hook: bool) -> Result<()> {
+    let chained = chained_path(path);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // ccmt's own hook takes over from a pass-through hook; it runs the repository hook itself
+    if is_shim(path) {
+        fs::remove_file(path)?;
+    }
+
+    // Older versions replaced the hook and kept a backup; chain it instead
+    let legacy_backup = path.with_extension("msg.bak");
+    if is_ccmt_hook(path) && legacy_backup.exists() && !chained.exists() {
+        fs::rename(&legacy_backup, &chained)?;
+    }
+
+    if path.exists() && !is_ccmt_hook(path) {
+        if chained.exists() {
+            bail!(
+                "Both {} and {} exist; remove one before installing",
+                path.display(),
+                chained.display()
+            );
+        }
+        fs::rename(path, &chained)
+            .with_context(|| format!("Failed to move existing hook to {}", chained.display()))?;
+        println!("Existing hook moved to {}", chained.display());
+    }
+
+    let mut hooks = Vec::new();
+    if chained.exists() {
+        hooks.push(format!("\"$(dirname \"$0\")/{kind}{CHAINED_SUFFIX}\""));
+    }
+    // A global core.hooksPath hides each repository's own hooks; keep running them
+    if repo_hook {
+        hooks.push(format!(
+            "\"$(git rev-parse --git-common-dir)/hooks/{kind}\""
+        ));
+    }
+    fs::write(path, hook_script(kind, mode, chain, &hooks))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    }
+
+    println!("Installed {kind} hook at {}", path.display());
+    if !hooks.is_empty() {
+        println!("Existing hooks run {chain} ccmt");
+    }
+    Ok(())
+}
+
+/// Install the hook, into a detected hook manager unless `native` is set
+pub fn install(kind: &str, mode: &str, chain: &str, native: bool) -> Result<()> {
+    if !native && let Some(fw) = framework::detect(&git::git_toplevel()?) {
+        println!("Detected {}; installing ccmt as an entry there", fw.name());
+        return fw.install(kind, mode);
+    }
+
+    // Honors core.hooksPath
+    let path = repo_hook_path(kind)?;
+    write_hook(&path, kind, mode, chain, false)
+}
+
+/// Where a per-repository install goes, refusing the directory a global core.hooksPath
+/// shares with every repository: rewriting or removing the hook there affects them all
+fn repo_hook_path(kind: &str) -> Result<PathBuf> {
+    let path = git::git_hook_path(kind)?;
+    if let Some(dir) = git::git_config_global_path("core.hooksPath")
+        && in_dir(&path, &dir)
+    {
+        bail!(
+            "{} is in the global hooks directory ({}), shared by every repository. \
+             Use `ccmt hook install --global` / `ccmt hook remove --global`, \
+             or set core.hooksPath for this repository only.",
+            path.display(),
+            dir.display()
+        );
+    }
+    Ok(path)
+}
+
+/// Whether `path` sits directly in `dir`, comparing resolved paths where they exist
+fn in_dir(path: &Path, dir: &Path) -> bool {
+    let resolve = |p: &Path| fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
+    path.parent()
+        .is_some_and(|parent| resolve(parent) == resolve(dir))
+}
+
+/// Install the hook for every repository, via core.hooksPath or init.templateDir
+pub fn install_global(kind: &str, mode: &str, chain: &str, template: bool) -> Result<()> {
+    if template {
+        let dir = template_dir()?;
+        write_hook(&dir.join("hooks").join(kind), kind, mode, chain, false)?;
+        git::git_config_set_global("init.templateDir", &dir.display().to_string())?;
+        println!("Set init.templateDir to {}", dir.display());
+        println!("New clones get the hook; run `git init` in existing repositories to add it");
+        return Ok(());
+    }
+
+    let (dir, own_dir) = match git::git_config_global_path("core.hooksPath") {
+        Some(dir) => {
+            let own = dir == global_hooks_dir()?;
+            (dir, own)
+        }
+        None => (global_hooks_dir()?, true),
+    };
+    write_hook(&dir.join(kind), kind, mode, chain, true)?;
+    // A directory the user set up keeps its hooks as they are; ccmt's own needs the rest of them
+    if own_dir {
+        let shims = write_shims(&dir)?;
+        if shims > 0 {
+            println!(
+                "Added {shims} pass-through hooks so repositories keep running their own hooks"
+            );
+        }
+    }
+    git::git_config_set_global("core.hooksPath", &dir.display().to_string())?;
+    println!("Set core.hooksPath to {}", dir.display());
+    Ok(())
+}
+
+/// Remove a ccmt hook script and restore the hook it chained; false when none was installed
+fn remove_hook(path: &Path) -> Result<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+
+    if !is_ccmt_hook(path) {
+        bail!(
+            "Hook at {} was not installed by ccmt. Remove manually if intended.",
+            path.display()
+        );
+    }
+
+    fs::remove_file(path)?;
+    println!("Removed hook at {}", path.display());
+
+    // Put back the hook ccmt was chaining (or the backup older versions made)
+    let chained = chained_path(path);
+    let legacy_backup = path.with_extension("msg.bak");
+    if let Some(previous) = [chained, legacy_backup].into_iter().find(|p| p.exists()) {
+        fs::rename(&previous, path)?;
+        println!("Restored previous hook from {}", previous.display());
+    }
+
+    Ok(true)
+}
+
+pub fn remove(kind: &str, native: bool) -> Result<()> {
+    if !native
+        && let Some(fw) = framework::detect(&git::git_toplevel()?)
+        && fw.remove(kind)?
+    {
+        return Ok(());
+    }
+
+    let path = repo_hook_path(kind)?;
+    if !remove_hook(&path)? {
+        println!("No hook found at {}", path.display());
+    }
+    Ok(())
+}
+
+/// Remove globally installed hooks, unsetting core.hooksPath when ccmt's directory is left empty
+pub fn remove_global(kind: &str) -> Result<()> {
+    let mut removed = false;
+
+    let default_dir = global_hooks_dir()?;
+    if let Some(dir) = git::git_config_global_path("core.hooksPath")
+        && is_ccmt_hook(&dir.join(kind))
+    {
+        removed |= remove_hook(&dir.join(kind))?;
+        if dir == default_dir {
+            write_shims(&dir)?;
+            if only_shims(&dir) {
+                git::git_config_unset_global("core.hooksPath")?;
+                fs::remove_dir_all(&dir)?;
+                println!("Unset core.hooksPath");
+            }
+        }
+    }
+
+    let template_hook = template_dir()?.join("hooks").join(kind);
+    if is_ccmt_hook(&template_hook) {
+        removed |= remove_hook(&template_hook)?;
+    }
+
+    if !removed {
+        println!("No global {kind} hook installed by ccmt");
+    }
+    Ok(())
+}
+
+/// Describe the hook script at `path` for `hook status`
+fn describe_hook(path: &Path) -> String {
+    let Ok(content) = fs::read_to_string(path) else {
+        return "not installed".to_string();
+    };
+    if content.contains(SHIM_MARKER) {
+        return "not installed (runs the repository's hook)".to_string();
+    }
+    if !content.contains(HOOK_MARKER) {
+        return "another hook (not ccmt)".to_string();
+    }
+    let chained = if chained_path(path).exists() {
+        ", chaining an existing hook"
+    } else {
+        ""
+    };
+    match script_version(&content) {
+        Some(v) if v == VERSION => format!("{} ({v}{chained})", "installed".green()),
+        Some(v) => format!(
+            "{} ({v}, current is {VERSION}{chained}; reinstall to update)",
+            "outdated".yellow()
+        ),
+        None => format!(
+            "{} (unversioned{chained}; reinstall to update)",
+            "outdated".yellow()
+        ),
+    }
+}
+
+/// Report where each hook is installed and whether it is current
+pub fn status() -> Result<()> {
+    let toplevel = git::git_toplevel().ok();
+    let global_dir = git::git_config_global_path("core.hooksPath");
+    let template_hooks = git::git_config_global_path("init.templateDir").map(|d| d.join("hooks"));
+
+    for kind in HOOK_KINDS {
+        println!("{}", kind.bold());
+
+        let mut repo_path = None;
+        if let Some(root) = &toplevel {
+            let path = git::git_hook_path(kind)?;
+            println!("  {}: {}", path.display(), describe_hook(&path));
+
+            // A ccmt hook in .git/hooks that git never runs
+            let default = git::git_default_hook_path(kind)?;
+            if default != path && is_ccmt_hook(&default) {
+                let hooks_path = git::git_config("core.hooksPath").unwrap_or_default();
+                println!(
+                    "  {} {} is shadowed by core.hooksPath ({hooks_path})",
+                    "warning:".yellow(),
+                    default.display()
+                );
+            }
+
+            if let Some(fw) = framework::detect(root) {
+                let entry = if fw.has_entry(kind) {
+                    "ccmt entry present".green().to_string()
+                } else {
+                    "no ccmt entry".to_string()
+                };
+                println!("  {}: {entry}", fw.name());
+            }
+            repo_path = Some(path);
+        } else {
+            println!("  not in a git repository");
+        }
+
+        // Skip the global directory when it is what the repository already uses
+        if let Some(dir) = &global_dir
+            && repo_path.as_ref() != Some(&dir.join(kind))
+        {
+            let path = dir.join(kind);
+            println!(
+                "  global core.hooksPath {}: {}",
+                path.display(),
+                describe_hook(&path)
+            );
+        }
+        if let Some(dir) = &template_hooks {
+            let path = dir.join(kind);
+            println!(
+                "  init.templateDir {}: {}",
+                path.display(),
+                describe_hook(&path)
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plan_generates_for_plain_commits() {
+        let expected = HookPlan::Generate {
+            amend: false,
+            template: false,
+        };
+        assert_eq!(plan(None, false), expected);
+        assert_eq!(plan(Some("merge"), false), expected);
+        assert_eq!(plan(Some("squash"), false), expected);
+    }
+
+    #[test]
+    fn plan_skips_user_messages() {
+        assert_eq!(plan(Some("message"), false), HookPlan::Skip);
+        assert_eq!(plan(Some("message"), true), HookPlan::Skip);
+    }
+
+    #[test]
+    fn plan_fills_templates() {
+        assert_eq!(
+            plan(Some("template"), false),
+            HookPlan::Generate {
+                amend: false,
+                template: true
+            }
+        );
+    }
+
+    #[test]
+    fn plan_amend_only_when_asked() {
+        assert_eq!(plan(Some("commit"), false), HookPlan::Skip);
+        assert_eq!(
+            plan(Some("commit"), true),
+            HookPlan::Generate {
+                amend: true,
+                template: false
+            }
+        );
+    }
+
+    #[test]
+    fn compose_keeps_comment_lines() {
+        let original = "Summary:\n\n# Please enter the commit message\n# On branch main\n";
+        assert_eq!(
+            compose("feat: add login", original, '#'),
+            "feat: add login\n\n# Please enter the commit message\n# On branch main\n"
+        );
+        assert_eq!(compose("feat: x", "", '#'), "feat: x\n");
+    }
+
+    #[test]
+    fn hook_scripts_delegate_to_hook_run() {
+        let script = hook_script("prepare-commit-msg", "reject", "after", &[]);
+        assert!(script.contains("ccmt hook run prepare-commit-msg \"$@\" || exit $?"));
+        assert!(!script.contains("grep"));
+        assert!(
+            hook_script("commit-msg", "repair", "after", &[])
+                .contains("ccmt hook run commit-msg --mode repair \"$@\"")
+        );
+    }
+
+    #[test]
+    fn hook_scripts_chain_existing_hooks() {
+        let hooks = ["\"$HOOKS/old\"".to_string()];
+        let run = "    \"$HOOKS/old\" \"$@\" || exit $?";
+        let before = hook_script("commit-msg", "reject", "before", &hooks);
+        let after = hook_script("commit-msg", "reject", "after", &hooks);
+        assert!(before.find(run) < before.find("command -v ccmt"));
+        assert!(after.find(run) > after.find("command -v ccmt"));
+        assert!(after.contains(&format!("grep -qF \"{HOOK_MARKER}\" \"$HOOKS/old\"")));
+    }
+
+    #[test]
+    fn hook_scripts_record_version() {
+        let script = hook_script("commit-msg", "reject", "after", &[]);
+        assert_eq!(script_version(&script), Some(VERSION));
+        assert_eq!(script_version("#!/bin/sh\n# Installed by ccmt\n"), None);
+    }
+
+    #[test]
+    fn shims_leave_existing_hooks_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let ccmt = dir.path().join("commit-msg");
+        fs::write(&ccmt, hook_script("commit-msg", "reject", "after", &[])).unwrap();
+
+        assert_eq!(write_shims(dir.path()).unwrap(), STANDARD_HOOKS.len() - 1);
+        assert!(is_ccmt_hook(&ccmt));
+        assert!(is_shim(&dir.path().join("pre-commit")));
+        assert!(!only_shims(dir.path()));
+        assert_eq!(write_shims(dir.path()).unwrap(), 0);
+
+        fs::remove_file(&ccmt).unwrap();
+        write_shims(dir.path()).unwrap();
+        assert!(only_shims(dir.path()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn shims_run_the_repository_hook() {
+        let repo = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .current_dir(repo.path())
+                .args(args)
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        let shims = tempfile::tempdir().unwrap();
+        write_shims(shims.path()).unwrap();
+        let run = || {
+            std::process::Command::new(shims.path().join("pre-push"))
+                .current_dir(repo.path())
+                .arg("origin")
+                .status()
+                .unwrap()
+        };
+        // No repository hook: nothing to run
+        assert!(run().success());
+
+        let hook = repo.path().join(".git/hooks/pre-push");
+        fs::write(&hook, "#!/bin/sh\n[ \"$1\" = origin ] && exit 7\n").unwrap();
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(run().code(), Some(7));
+    }
+
+    #[test]
+    fn global_hooks_dir_is_recognized() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(in_dir(&dir.path().join("commit-msg"), dir.path()));
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        let nested = dir.path().join("sub").join("..");
+        assert!(in_dir(&dir.path().join("commit-msg"), &nested));
+        assert!(!in_dir(
+            Path::new("/repo/.git/hooks/commit-msg"),
+            dir.path()
+        ));
+    }
+
+    #[test]
+    fn chained_path_appends_suffix() {
+        assert_eq!(
+            chained_path(Path::new(".git/hooks/commit-msg")),
+            PathBuf::from(".git/hooks/commit-msg.ccmt-chained")
+        );
+    }
+}
@@ -0,0 +1,270 @@
+use crate::config::Config;
+use crate::format::{self, Rules, Violation};
+use crate::prompt::{self, RepoContext};
+use crate::{git, provider};
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Serialize;
+use std::path::Path;
+
+/// Marker line below which `git commit --verbose` appends the diff
+const SCISSORS: &str = "------------------------ >8 ------------------------";
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    /// Commit hash, or `None` when linting a file
+    pub commit: Option<String>,
+    pub subject: String,
+    pub violations: Vec<Violation>,
+}
+
+#[derive(Debug, Serialize)]
+struct Output<'a> {
+    valid: bool,
+    results: &'a [Report],
+}
+
+/// Message text as git would commit it: comments and the verbose diff removed
+pub fn clean_message_file(content: &str, comment_char: char) -> String {
+    let content = match content
+        .lines()
+        .position(|l| l.starts_with(comment_char) && l.contains(SCISSORS))
+    {
+        Some(idx) => content.lines().take(idx).collect::<Vec<_>>().join("\n"),
+        None => content.to_string(),
+    };
+    format::strip_comments(&content, comment_char)
+}
+
+pub fn lint_message(commit: Option<String>, msg: &str, rules: &Rules) -> Report {
+    Report {
+        commit,
+        subject: msg.lines().next().unwrap_or_default().to_string(),
+        violations: format::lint(msg, rules),
+    }
+}
+
+/// Lint a message file, or the commits in `range` (default: the last commit)
+pub fn collect(config: &Config, range: Option<&str>, file: Option<&Path>) -> Result<Vec<Report>> {
+    let rules = Rules::from_config(config);
+
+    if let Some(path) = file {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let msg = clean_message_file(&content, git::git_comment_char());
+        return Ok(vec![lint_message(None, &msg, &rules)]);
+    }
+
+    let commits = match range {
+        Some(r) if r.contains("..") => git::git_log_commits(r)?,
+        rev => {
+            let hash = git::git_rev_parse(rev.unwrap_or("HEAD"))?;
+            let msg = git::git_commit_message(&hash)?;
+            vec![(hash, msg)]
+        }
+    };
+    Ok(commits
+        .into_iter()
+        .rev()
+        .map(|(hash, msg)| lint_message(Some(hash), &msg, &rules))
+        .collect())
+}
+
+/// Print reports; returns true when every message passed
+pub fn print(reports: &[Report], format: &str) -> Result<bool> {
+    let valid = reports.iter().all(|r| r.violations.is_empty());
+
+    if format == "json" {
+        let output = Output {
+            valid,
+            results: reports,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(valid);
+    }
+
+    for report in reports {
+        let label = match &report.commit {
+            Some(hash) => format!("{} {}", &hash[..hash.len().min(7)], report.subject),
+            None => report.subject.clone(),
+        };
+        if report.violations.is_empty() {
+            println!("{} {label}", "✓".green());
+            continue;
+        }
+        println!("{} {label}", "✗".red());
+        for v in &report.violations {
+            println!(
+                "    {}:{} {} {}",
+                "line".dimmed(),
+                v.line,
+                format!("[{}]", v.rule).yellow(),
+                v.message
+            );
+        }
+    }
+
+    let failed = reports.iter().filter(|r| !r.violations.is_empty()).count();
+    if failed > 0 {
+        println!();
+        println!(
+            "{}",
+            format!("{failed} of {} messages have problems.", reports.len()).red()
+        );
+    }
+    Ok(valid)
+}
+
+/// Line-level diff (LCS) as (marker, line) pairs: ' ' kept, '-' removed, '+' added
+pub fn line_diff<'a>(old: &'a str, new: &'a str) -> Vec<(char, &'a str)> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::new();
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            out.push((' ', a[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            out.push(('-', a[i]));
+            i += 1;
+        } else {
+            out.push(('+', b[j]));
+            j += 1;
+        }
+    }
+    out.extend(a[i..].iter().map(|l| ('-', *l)));
+    out.extend(b[j..].iter().map(|l| ('+', *l)));
+    out
+}
+
+/// Ask the AI to rewrite a failing message file, show the change, and re-lint it
+fn repair(config: &Config, path: &Path, report: &Report) -> Result<bool> {
+    let rules = Rules::from_config(config);
+    let content = std::fs::read_to_string(path)?;
+    let original = clean_message_file(&content, git::git_comment_char());
+    let problems: Vec<String> = report
+        .violations
+        .iter()
+        .map(|v| v.message.clone())
+        .collect();
+
+    let provider = provider::from_config(config)?;
+    let system = prompt::build_system_prompt(config, &RepoContext::default());
+    eprintln!("{}", "Repairing commit message...".dimmed());
+    let raw = provider.generate(
+        &prompt::build_repair_prompt(&original, &problems),
+        &system,
+        &config.auth.model,
+    )?;
+
+    // Never lose the author's trailers, even if the model dropped them
+    let mut repaired = format::format_message(&raw, &rules);
+    let (_, trailers) = format::split_trailers(&original);
+    for (key, value) in trailers.lines().filter_map(format::parse_trailer) {
+        repaired = format::append_trailer(&repaired, key, value);
+    }
+
+    eprintln!("{}", "Commit message was rewritten:".bold());
+    for (marker, line) in line_diff(&original, &repaired) {
+        match marker {
+            '-' => eprintln!("{}", format!("- {line}").red()),
+            '+' => eprintln!("{}", format!("+ {line}").green()),
+            _ => eprintln!("  {line}"),
+        }
+    }
+
+    let remaining = format::lint(&repaired, &rules);
+    if !remaining.is_empty() {
+        let rejected = Report {
+            commit: None,
+            subject: repaired.lines().next().unwrap_or_default().to_string(),
+            violations: remaining,
+        };
+        print(&[rejected], "text")?;
+        return Ok(false);
+    }
+
+    std::fs::write(path, format!("{repaired}\n"))?;
+    Ok(true)
+}
+
+pub fn run(
+    config: &Config,
+    range: Option<&str>,
+    file: Option<&Path>,
+    repair_file: bool,
+    format: &str,
+) -> Result<bool> {
+    let reports = collect(config, range, file)?;
+    if repair_file
+        && let (Some(path), [report]) = (file, reports.as_slice())
+        && !report.violations.is_empty()
+    {
+        return repair(config, path, report);
+    }
+    print(&reports, format)
+}
+
+/// Exit status for `ccmt lint`: 1 when a message breaks the rules, 2 when linting
+/// itself failed (bad range, unreadable file, git error), so CI can tell them apart
+pub fn exit_code(result: &Result<bool>) -> i32 {
+    match result {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(_) => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_exit_differently_from_violations() {
+        assert_eq!(exit_code(&Ok(true)), 0);
+        assert_eq!(exit_code(&Ok(false)), 1);
+        assert_eq!(exit_code(&Err(anyhow::anyhow!("bad range"))), 2);
+    }
+
+    #[test]
+    fn cleans_comments_and_verbose_diff() {
+        let content = "feat: add login\n\n# Please enter the commit message\n\
+                       # ------------------------ >8 ------------------------\n\
+                       diff --git a/x b/x\n";
+        assert_eq!(clean_message_file(content, '#'), "feat: add login");
+    }
+
+    #[test]
+    fn diff_marks_changed_lines() {
+        let diff = line_diff("Add login\n\nBody", "feat: add login\n\nBody");
+        assert_eq!(
+            diff,
+            vec![
+                ('-', "Add login"),
+                ('+', "feat: add login"),
+                (' ', ""),
+                (' ', "Body"),
+            ]
+        );
+    }
+
+    #[test]
+    fn report_collects_violations() {
+        let report = lint_message(None, "Add login", &Rules::default());
+        assert_eq!(report.subject, "Add login");
+        assert_eq!(report.violations[0].rule, "header-format");
+    }
+}
@@ -1,108 +1,325 @@
+mod auth;
+mod changelog;
 mod cli;
 mod config;
 mod format;
 mod git;
 mod hook;
+mod lint;
+mod pr;
 mod prompt;
 mod provider;
+mod release;
+mod ticket;
 
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
 use clap::Parser;
-use cli::{Cli, Commands, ConfigAction, HookAction};
+use cli::{AuthAction, Cli, Commands, ConfigAction, HookAction};
 use colored::Colorize;
-use dialoguer::{Input, Select};
+use config::Config;
+use dialoguer::{Editor, Input, Select};
+use provider::Provider;
+use std::io::IsTerminal;
 
 fn main() {
     if let Err(e) = run() {
-        eprintln!("{} {e:#}", "error:".red().bold());
+        print_error(&e);
         std::process::exit(1);
     }
 }
 
+fn print_error(e: &anyhow::Error) {
+    eprintln!("{} {e:#}", "error:".red().bold());
+}
+
 fn run() -> Result<()> {
-    let cli = Cli::parse();
+    let cli = Cli::parse_from(cli::normalize_args(std::env::args_os()));
 
     // Handle subcommands
     if let Some(cmd) = &cli.command {
         return match cmd {
             Commands::Config { action } => match action {
-                ConfigAction::Init => config::init_config(),
-                ConfigAction::Show => config::show_config(
+                ConfigAction::Init { project } => {
+                    config::init_config(*project, !cli.no_confirm && std::io::stdin().is_terminal())
+                }
+                ConfigAction::Show { origin } => config::show_config(
                     cli.provider.as_deref(),
                     cli.model.as_deref(),
                     cli.language.as_deref(),
+                    cli.profile.as_deref(),
+                    *origin,
                 ),
+                ConfigAction::Check => {
+                    if !config::check_config()? {
+                        std::process::exit(1);
+                    }
+                    Ok(())
+                }
+                ConfigAction::Get { key } => {
+                    let cfg = config::load_config(
+                        cli.provider.as_deref(),
+                        cli.model.as_deref(),
+                        cli.language.as_deref(),
+                        cli.profile.as_deref(),
+                    )?;
+                    config::get_config(&cfg, key)
+                }
+                ConfigAction::Set {
+                    key,
+                    value,
+                    project,
+                    ..
+                } => config::set_config(key, value, *project),
+                ConfigAction::Unset { key, project, .. } => config::unset_config(key, *project),
+                ConfigAction::Edit { project, .. } => config::edit_config(*project),
+                ConfigAction::Migrate { project, .. } => config::migrate_config(*project),
+                ConfigAction::Schema => config::print_schema(),
             },
             Commands::Hook { action } => match action {
-                HookAction::Install => hook::install(),
-                HookAction::Remove => hook::remove(),
+                HookAction::Install {
+                    kind,
+                    mode,
+                    chain,
+                    native,
+                    global,
+                    template,
+                } => {
+                    if *global {
+                        hook::install_global(kind, mode, chain, *template)
+                    } else {
+                        hook::install(kind, mode, chain, *native)
+                    }
+                }
+                HookAction::Remove {
+                    kind,
+                    native,
+                    global,
+                } => {
+                    if *global {
+                        hook::remove_global(kind)
+                    } else {
+                        hook::remove(kind, *native)
+                    }
+                }
+                HookAction::Status => hook::status(),
+                HookAction::Run { kind, mode, args } => match kind.as_str() {
+                    "commit-msg" => {
+                        let Some(file) = args.first() else {
+                            bail!("Usage: ccmt hook run commit-msg <file>");
+                        };
+                        let cfg = load_config(&cli)?;
+                        let path = std::path::Path::new(file);
+                        if !lint::run(&cfg, None, Some(path), mode == "repair", "text")? {
+                            std::process::exit(1);
+                        }
+                        Ok(())
+                    }
+                    _ => {
+                        // Never block a commit because generation failed
+                        if let Err(e) = prepare_commit_msg(&cli, args) {
+                            eprintln!("{} {e:#}", "ccmt:".yellow().bold());
+                        }
+                        Ok(())
+                    }
+                },
+            },
+            Commands::Squash { base } => squash(&cli, base),
+            Commands::Pr { base, format } => {
+                let cfg = load_config(&cli)?;
+                pr::run(&cfg, base.as_deref(), format, cli.message.as_deref())
+            }
+            Commands::Changelog {
+                range,
+                polish,
+                prepend,
+            } => {
+                let cfg = load_config(&cli)?;
+                changelog::run(&cfg, range, *polish, prepend.as_deref())
+            }
+            Commands::Lint {
+                range,
+                file,
+                repair,
+                format,
+            } => {
+                let result = load_config(&cli).and_then(|cfg| {
+                    lint::run(&cfg, range.as_deref(), file.as_deref(), *repair, format)
+                });
+                if let Err(e) = &result {
+                    print_error(e);
+                }
+                match lint::exit_code(&result) {
+                    0 => Ok(()),
+                    code => std::process::exit(code),
+                }
+            }
+            Commands::Auth { action } => match action {
+                AuthAction::Login => {
+                    let cfg = config::load_config(
+                        cli.provider.as_deref(),
+                        cli.model.as_deref(),
+                        cli.language.as_deref(),
+                        cli.profile.as_deref(),
+                    )?;
+                    auth::login(&cfg)
+                }
+                AuthAction::Logout => auth::logout(),
             },
+            Commands::Release { tag } => {
+                let cfg = load_config(&cli)?;
+                let confirm = cfg.commit.confirm && !cli.no_confirm;
+                let sign = cli.gpg_sign.as_ref().map(|k| k.as_deref());
+                release::run(&cfg, *tag, confirm, sign, cli.message.as_deref())
+            }
         };
     }
 
-    // Main commit flow
-    if !git::is_git_repo() {
-        bail!("Not a git repository. Run this from inside a git repo.");
-    }
+    commit(&cli)
+}
 
-    let cfg = config::load_config(
-        cli.provider.as_deref(),
-        cli.model.as_deref(),
-        cli.language.as_deref(),
-    )?;
+/// State shared by every flow that generates and commits a message
+struct Session<'a> {
+    cli: &'a Cli,
+    cfg: Config,
+    provider: Box<dyn Provider>,
+    system_prompt: String,
+    comment_char: char,
+    tickets: Vec<String>,
+    ticket_position: ticket::Position,
+    trailers: Vec<(String, String)>,
+}
 
-    // Auto-stage if configured
-    if cfg.commit.auto_stage {
-        git::git_stage_all()?;
-    }
+impl<'a> Session<'a> {
+    /// `template` is raw commit template text; comment lines are removed here
+    fn new(cli: &'a Cli, cfg: Config, template: Option<String>) -> Result<Self> {
+        let ticket_position = ticket::Position::parse(&cfg.commit.ticket_position)?;
+        let provider = provider::from_config(&cfg)?;
 
-    // Get diff
-    let diff = git::git_diff_staged()?;
-    if diff.is_empty() {
-        // Try unstaged diff as fallback info
-        let unstaged = git::git_diff_all()?;
-        if unstaged.is_empty() {
-            bail!("No changes to commit. Stage changes with 'git add' first.");
-        }
-        bail!(
-            "No staged changes. Stage changes with 'git add' first, or set auto_stage = true in config."
-        );
-    }
+        let examples = if cfg.prompt.history_examples > 0 {
+            let paths = if cfg.prompt.history_same_paths {
+                git::git_staged_files().unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+            git::git_recent_messages(cfg.prompt.history_examples, &paths).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
 
-    let status = git::git_status().unwrap_or_default();
+        let comment_char = git::git_comment_char();
+        let template = template
+            .map(|t| format::strip_comments(&t, comment_char))
+            .filter(|t| !t.is_empty());
 
-    // Resolve API key from config or env
-    let api_key = if cfg.auth.api_key.is_empty() {
-        std::env::var("ANTHROPIC_API_KEY").unwrap_or_default()
-    } else {
-        cfg.auth.api_key.clone()
-    };
+        let scopes = if cfg.commit.conventional && cfg.commit.scopes.infer() {
+            let subjects: Vec<String> = git::git_recent_messages(prompt::SCOPE_HISTORY, &[])
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|m| m.lines().next().map(str::to_string))
+                .collect();
+            prompt::common_scopes(&subjects, prompt::INFERRED_SCOPES)
+        } else {
+            Vec::new()
+        };
 
-    let provider = provider::create_provider(&cfg.auth.provider, &api_key)?;
+        let repo = prompt::RepoContext {
+            examples,
+            template,
+            scopes,
+        };
+        let system_prompt = prompt::build_system_prompt(&cfg, &repo);
 
-    let system_prompt = prompt::build_system_prompt(&cfg);
-    let user_prompt = prompt::build_user_prompt(
-        &diff,
-        &status,
-        cli.message.as_deref(),
-        cfg.prompt.max_diff_length,
-    );
+        let tickets = if ticket_position == ticket::Position::None {
+            Vec::new()
+        } else {
+            let branch = git::git_current_branch().unwrap_or_default();
+            ticket::extract_tickets(&branch, &cfg.commit.ticket_patterns)?
+        };
+        let mut trailers = Vec::new();
+        for t in &cfg.commit.trailers {
+            let (key, value) = format::parse_trailer(t).with_context(|| {
+                format!("Invalid trailer in config: {t:?}. Use \"Key: value\".")
+            })?;
+            trailers.push((key.to_string(), value.to_string()));
+        }
+        for author in &cli.co_authors {
+            trailers.push(("Co-authored-by".to_string(), author.clone()));
+        }
+        if cfg.commit.signoff || cli.signoff {
+            trailers.push(("Signed-off-by".to_string(), git::git_user_ident()?));
+        }
 
-    // Generate initial message
-    eprintln!("{}", "Generating commit message...".dimmed());
-    let raw = provider.generate(&user_prompt, &system_prompt, &cfg.auth.model)?;
-    let mut message = format::format_message(&raw);
+        Ok(Self {
+            cli,
+            cfg,
+            provider,
+            system_prompt,
+            comment_char,
+            tickets,
+            ticket_position,
+            trailers,
+        })
+    }
 
-    // Dry-run mode
-    if cli.dry_run {
-        println!("{message}");
-        return Ok(());
+    /// Ask the provider for a message and clean up the response
+    ///
+    /// A type or scope outside the configured lists gets one retry; if it persists,
+    /// the violations are shown before review.
+    fn generate(&self, user_prompt: &str) -> Result<String> {
+        let rules = format::Rules::from_config(&self.cfg);
+        let model = &self.cfg.auth.model;
+        let raw = self
+            .provider
+            .generate(user_prompt, &self.system_prompt, model)?;
+        let message = format::format_message(&raw, &rules);
+        let violations = format::vocabulary_violations(&message, &rules);
+        if violations.is_empty() {
+            return Ok(message);
+        }
+
+        eprintln!(
+            "{}",
+            "Message uses a type or scope outside the configured list; regenerating...".dimmed()
+        );
+        let problems: Vec<String> = violations.into_iter().map(|v| v.message).collect();
+        let raw = self.provider.generate(
+            &prompt::build_repair_prompt(&message, &problems),
+            &self.system_prompt,
+            model,
+        )?;
+        let message = format::format_message(&raw, &rules);
+        for v in format::vocabulary_violations(&message, &rules) {
+            eprintln!("{} {}", "warning:".yellow().bold(), v.message);
+        }
+        Ok(message)
+    }
+
+    /// Apply the deterministic parts: ticket IDs and trailers
+    fn finalize(&self, message: String) -> Result<String> {
+        let mut message = ticket::apply_tickets(
+            &message,
+            &self.tickets,
+            self.ticket_position,
+            &format::Rules::from_config(&self.cfg),
+        );
+        for (key, value) in &self.trailers {
+            message = format::append_trailer(&message, key, value);
+        }
+        Ok(message)
     }
 
-    // Confirmation loop
-    let should_confirm = cfg.commit.confirm && !cli.no_confirm;
+    /// Confirmation loop. `edit_prompt` builds a revision prompt from the
+    /// previous message and the user's instruction. Returns `None` when cancelled.
+    fn review(
+        &self,
+        mut message: String,
+        edit_prompt: impl Fn(&str, &str) -> String,
+    ) -> Result<Option<String>> {
+        let should_confirm = self.cfg.commit.confirm && !self.cli.no_confirm;
+        if !should_confirm {
+            return Ok(Some(message));
+        }
 
-    if should_confirm {
         loop {
             // Display message
             eprintln!();
@@ -115,6 +332,7 @@ fn run() -> Result<()> {
             let choices = &[
                 "Yes - commit with this message",
                 "Edit - revise the message",
+                "Manual - edit in your editor",
                 "No - cancel",
             ];
             let selection = Select::new()
@@ -124,46 +342,240 @@ fn run() -> Result<()> {
                 .interact_opt()?;
 
             match selection {
-                Some(0) => break, // Yes
+                Some(0) => return Ok(Some(message)), // Yes
                 Some(1) => {
                     // Edit
                     let instruction: String = Input::new()
                         .with_prompt("Describe what to change")
                         .interact_text()?;
 
-                    let edit_prompt = prompt::build_edit_prompt(
-                        &diff,
-                        &status,
-                        &message,
-                        &instruction,
-                        cli.message.as_deref(),
-                        cfg.prompt.max_diff_length,
-                    );
-
                     eprintln!("{}", "Regenerating...".dimmed());
-                    let raw = provider.generate(&edit_prompt, &system_prompt, &cfg.auth.model)?;
-                    message = format::format_message(&raw);
+                    let revised = self.generate(&edit_prompt(&message, &instruction))?;
+                    message = self.finalize(revised)?;
                 }
-                Some(2) | None => {
+                Some(2) => {
+                    // A subject such as "#42 fix login" must survive the comment strip
+                    let c = format::comment_char_for(&message, self.comment_char);
+                    let buffer = format!(
+                        "{message}\n\n\
+                         {c} Edit the commit message above. Lines starting with '{c}' are ignored.\n\
+                         {c} An empty message keeps the previous one.\n"
+                    );
+                    let mut editor = Editor::new();
+                    if let Ok(cmd) = git::git_editor() {
+                        editor.executable(cmd);
+                    }
+                    if let Some(edited) = editor.edit(&buffer)? {
+                        let edited = format::strip_comments(&edited, c);
+                        if !edited.is_empty() {
+                            message = edited;
+                        }
+                    }
+                }
+                Some(3) | None => {
                     // No or Ctrl+C
                     eprintln!("{}", "Cancelled.".yellow());
-                    return Ok(());
+                    return Ok(None);
                 }
                 _ => unreachable!(),
             }
         }
     }
 
-    // Commit
-    git::git_commit(&message)?;
-    eprintln!(
-        "{} {}",
-        "Committed:".green().bold(),
-        message.lines().next().unwrap_or("")
-    );
+    fn commit(&self, message: &str) -> Result<()> {
+        let commit_opts = git::CommitOptions {
+            gpg_sign: self.cli.gpg_sign.clone(),
+            no_verify: self.cli.no_verify,
+            author: self.cli.author.clone(),
+            date: self.cli.date.clone(),
+            extra_args: self.cli.git_args.clone(),
+        };
+        git::git_commit(message, &commit_opts)?;
+        eprintln!(
+            "{} {}",
+            "Committed:".green().bold(),
+            message.lines().next().unwrap_or("")
+        );
+        Ok(())
+    }
+}
+
+fn load_config(cli: &Cli) -> Result<Config> {
+    if !git::is_git_repo() {
+        bail!("Not a git repository. Run this from inside a git repo.");
+    }
+
+    config::load_config(
+        cli.provider.as_deref(),
+        cli.model.as_deref(),
+        cli.language.as_deref(),
+        cli.profile.as_deref(),
+    )
+}
+
+/// A generated message plus the context needed to revise it
+struct Draft<'a> {
+    session: Session<'a>,
+    message: String,
+    diff: String,
+    status: String,
+    hint: Option<String>,
+}
+
+impl Draft<'_> {
+    fn edit_prompt(&self, previous: &str, instruction: &str) -> String {
+        prompt::build_edit_prompt(
+            &self.diff,
+            &self.status,
+            previous,
+            instruction,
+            self.hint.as_deref(),
+            self.session.cfg.prompt.max_diff_length,
+        )
+    }
+}
+
+/// Generate a message for the staged changes. With `amend`, the diff covers
+/// the whole commit being amended rather than just the new changes.
+fn draft<'a>(
+    cli: &'a Cli,
+    cfg: Config,
+    amend: bool,
+    template: Option<String>,
+) -> Result<Draft<'a>> {
+    let in_progress = git::git_in_progress();
+
+    // Get diff (a merge may legitimately have none, e.g. with -s ours)
+    let diff = if amend {
+        git::git_diff_amend()?
+    } else {
+        git::git_diff_staged()?
+    };
+    if diff.is_empty() && !matches!(in_progress, Some(git::InProgress::Merge(_))) {
+        // Try unstaged diff as fallback info
+        let unstaged = git::git_diff_all()?;
+        if unstaged.is_empty() {
+            bail!("No changes to commit. Stage changes with 'git add' first.");
+        }
+        bail!(
+            "No staged changes. Stage changes with 'git add' first, or set auto_stage = true in config."
+        );
+    }
+
+    let status = git::git_status().unwrap_or_default();
+    let session = Session::new(cli, cfg, template)?;
+    let comment_char = session.comment_char;
+    let max_diff_length = session.cfg.prompt.max_diff_length;
+
+    // During a rebase, the original message of the replayed commit is useful context
+    let hint = match in_progress {
+        Some(git::InProgress::Rebase) => git::git_state_file("rebase-merge/message")
+            .map(|m| prompt::rebase_hint(m.trim(), cli.message.as_deref())),
+        _ => None,
+    }
+    .or_else(|| cli.message.clone());
+
+    // Generate initial message
+    eprintln!("{}", "Generating commit message...".dimmed());
+    let message =
+        match &in_progress {
+            Some(git::InProgress::Merge(head)) => {
+                let merge_msg = git::git_state_file("MERGE_MSG").unwrap_or_default();
+                let subject = format::strip_comments(&merge_msg, comment_char)
+                    .lines()
+                    .next()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("Merge commit '{head}'"));
+                let commits = git::git_log_subjects(&format!("HEAD..{head}")).unwrap_or_default();
+                let conflicts = git::parse_merge_conflicts(&merge_msg, comment_char);
+                session.generate(&prompt::build_merge_prompt(
+                    &subject,
+                    &commits,
+                    &conflicts,
+                    &diff,
+                    hint.as_deref(),
+                    max_diff_length,
+                ))?
+            }
+            Some(git::InProgress::Revert(head)) => {
+                let reverted = git::git_commit_message(head)?;
+                let subject = reverted.lines().next().unwrap_or_default();
+                let mut message = format!("Revert \"{subject}\"\n\nThis reverts commit {head}.");
+                if let Some(h) = hint.as_deref() {
+                    let reason_prompt =
+                        prompt::build_revert_reason_prompt(&reverted, &diff, h, max_diff_length);
+                    let raw = session.provider.generate(
+                        &reason_prompt,
+                        &session.system_prompt,
+                        &session.cfg.auth.model,
+                    )?;
+                    message.push_str("\n\n");
+                    message.push_str(raw.trim());
+                }
+                message
+            }
+            Some(git::InProgress::CherryPick(head)) => {
+                // MERGE_MSG already carries the "(cherry picked from commit …)" line when -x was used
+                let merge_msg = git::git_state_file("MERGE_MSG")
+                    .map(|m| format::strip_comments(&m, comment_char))
+                    .unwrap_or_default();
+                if merge_msg.is_empty() {
+                    git::git_commit_message(head)?
+                } else {
+                    merge_msg
+                }
+            }
+            Some(git::InProgress::Squash) => {
+                let squash_msg = git::git_state_file("SQUASH_MSG").unwrap_or_default();
+                session.generate(&prompt::build_squash_prompt(
+                    &format::strip_comments(&squash_msg, comment_char),
+                    &diff,
+                    hint.as_deref(),
+                    max_diff_length,
+                ))?
+            }
+            Some(git::InProgress::Rebase) | None => session.generate(
+                &prompt::build_user_prompt(&diff, &status, hint.as_deref(), max_diff_length),
+            )?,
+        };
+    let message = session.finalize(message)?;
+
+    Ok(Draft {
+        session,
+        message,
+        diff,
+        status,
+        hint,
+    })
+}
+
+/// Main commit flow
+fn commit(cli: &Cli) -> Result<()> {
+    let cfg = load_config(cli)?;
+
+    // Auto-stage if configured
+    if cfg.commit.auto_stage {
+        git::git_stage_all()?;
+    }
+
+    let draft = draft(cli, cfg, false, git::git_commit_template())?;
+    let session = &draft.session;
+
+    // Dry-run mode
+    if cli.dry_run {
+        println!("{}", draft.message);
+        return Ok(());
+    }
+
+    let edit_prompt = |previous: &str, instruction: &str| draft.edit_prompt(previous, instruction);
+    let Some(message) = session.review(draft.message.clone(), edit_prompt)? else {
+        return Ok(());
+    };
+
+    session.commit(&message)?;
 
     // Auto-push
-    let should_push = cfg.commit.auto_push || cli.push;
+    let should_push = session.cfg.commit.auto_push || cli.push;
     if should_push {
         eprintln!("{}", "Pushing...".dimmed());
         git::git_push()?;
@@ -172,3 +584,97 @@ fn run() -> Result<()> {
 
     Ok(())
 }
+
+/// prepare-commit-msg hook: fill in the message file according to the commit source
+fn prepare_commit_msg(cli: &Cli, args: &[String]) -> Result<()> {
+    let Some(file) = args.first() else {
+        bail!("Usage: ccmt hook run prepare-commit-msg <file> [source] [sha]");
+    };
+    // The pre-commit framework passes the source through the environment
+    let env_source = std::env::var("PRE_COMMIT_COMMIT_MSG_SOURCE").ok();
+    let source = args.get(1).or(env_source.as_ref()).map(String::as_str);
+    let amend_requested = std::env::var("CCMT_AMEND").is_ok_and(|v| v == "1");
+
+    let hook::HookPlan::Generate { amend, template } = hook::plan(source, amend_requested) else {
+        return Ok(());
+    };
+
+    let original = std::fs::read_to_string(file)?;
+    let comment_char = git::git_comment_char();
+    // With -t or commit.template, the file holds the template to fill in
+    let template = if template {
+        Some(original.clone())
+    } else {
+        git::git_commit_template()
+    };
+
+    let cfg = load_config(cli)?;
+    let draft = draft(cli, cfg, amend, template)?;
+    std::fs::write(file, hook::compose(&draft.message, &original, comment_char))?;
+    Ok(())
+}
+
+/// Squash `base..HEAD` into a single commit with a synthesized message
+fn squash(cli: &Cli, base: &str) -> Result<()> {
+    let cfg = load_config(cli)?;
+
+    let head_rev = git::git_rev_parse("HEAD")?;
+    // Squash onto the fork point: resetting to a base that moved on would revert its new commits
+    let base_rev = git::git_merge_base(base, &head_rev)
+        .with_context(|| format!("{base} and HEAD have no common ancestor"))?;
+    if !git::git_diff_staged()?.is_empty() {
+        bail!("You have staged changes. Commit or unstage them before squashing.");
+    }
+
+    let range = format!("{base_rev}..{head_rev}");
+    let messages = git::git_log_messages(&range)?;
+    if messages.is_empty() {
+        bail!("No commits to squash between {base} and HEAD.");
+    }
+    let diff = git::git_diff_range(&base_rev, &head_rev)?;
+
+    let session = Session::new(cli, cfg, git::git_commit_template())?;
+    let cfg = &session.cfg;
+    let hint = cli.message.as_deref();
+    let log = messages.join("\n\n---\n\n");
+
+    eprintln!(
+        "{}",
+        format!("Squashing {} commits...", messages.len()).dimmed()
+    );
+    let message = session.generate(&prompt::build_squash_prompt(
+        &log,
+        &diff,
+        hint,
+        cfg.prompt.max_diff_length,
+    ))?;
+    let message = session.finalize(message)?;
+
+    if cli.dry_run {
+        println!("{message}");
+        return Ok(());
+    }
+
+    let edit_prompt = |previous: &str, instruction: &str| {
+        prompt::build_edit_prompt(
+            &diff,
+            "",
+            previous,
+            instruction,
+            hint,
+            cfg.prompt.max_diff_length,
+        )
+    };
+    let Some(message) = session.review(message, edit_prompt)? else {
+        return Ok(());
+    };
+
+    git::git_reset_soft(&base_rev)?;
+    if let Err(e) = session.commit(&message) {
+        // Put the branch back where it was so nothing is lost
+        git::git_reset_soft(&head_rev)?;
+        return Err(e.context(format!("Squash aborted; HEAD restored to {head_rev}")));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use crate::config::Config;
+use crate::{git, prompt, provider};
+use anyhow::{Result, bail};
+use colored::Colorize;
+use serde::Serialize;
+
+/// Template locations GitHub recognizes, relative to the repository root
+const TEMPLATE_PATHS: &[&str] = &[
+    ".github/pull_request_template.md",
+    ".github/PULL_REQUEST_TEMPLATE.md",
+    "pull_request_template.md",
+    "PULL_REQUEST_TEMPLATE.md",
+    "docs/pull_request_template.md",
+    "docs/PULL_REQUEST_TEMPLATE.md",
+];
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct PullRequest {
+    pub title: String,
+    pub body: String,
+}
+
+/// Split model output into a title (first line) and Markdown body
+pub fn parse_output(raw: &str) -> PullRequest {
+    let raw = raw.trim();
+    let (title, body) = raw.split_once('\n').unwrap_or((raw, ""));
+    let title = title.trim().trim_start_matches('#').trim();
+    let title = title
+        .strip_prefix("Title:")
+        .or_else(|| title.strip_prefix("title:"))
+        .unwrap_or(title)
+        .trim();
+    PullRequest {
+        title: title.to_string(),
+        body: body.trim().to_string(),
+    }
+}
+
+fn find_template() -> Option<String> {
+    let root = git::git_toplevel().ok()?;
+    TEMPLATE_PATHS
+        .iter()
+        .find_map(|p| std::fs::read_to_string(root.join(p)).ok())
+        .filter(|t| !t.trim().is_empty())
+}
+
+pub fn run(config: &Config, base: Option<&str>, format: &str, hint: Option<&str>) -> Result<()> {
+    let base = base.map_or_else(git::git_default_branch, str::to_string);
+    let fork_point = git::git_merge_base(&base, "HEAD")?;
+
+    let range = format!("{fork_point}..HEAD");
+    let messages = git::git_log_messages(&range)?;
+    if messages.is_empty() {
+        bail!("No commits on this branch relative to {base}.");
+    }
+    let diff = git::git_diff_range(&fork_point, "HEAD")?;
+
+    let provider = provider::from_config(config)?;
+    let system_prompt = prompt::build_pr_system_prompt(config);
+    let template = find_template();
+    let user_prompt = prompt::build_pr_prompt(
+        &messages.join("\n\n---\n\n"),
+        &diff,
+        template.as_deref(),
+        hint,
+        config.prompt.max_diff_length,
+    );
+
+    eprintln!("{}", "Generating pull request description...".dimmed());
+    let raw = provider.generate(&user_prompt, &system_prompt, &config.auth.model)?;
+    let pr = parse_output(&raw);
+
+    match format {
+        "json" => println!("{}", serde_json::to_string_pretty(&pr)?),
+        _ => println!("{}\n\n{}", pr.title, pr.body),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_title_and_body() {
+        let pr = parse_output("feat: add login\n\n## Summary\nAdds login.\n");
+        assert_eq!(pr.title, "feat: add login");
+        assert_eq!(pr.body, "## Summary\nAdds login.");
+    }
+
+    #[test]
+    fn strips_heading_and_label_from_title() {
+        assert_eq!(
+            parse_output("# Title: Add login\n\nBody").title,
+            "Add login"
+        );
+    }
+
+    #[test]
+    fn title_only() {
+        let pr = parse_output("fix: typo");
+        assert_eq!(pr.title, "fix: typo");
+        assert!(pr.body.is_empty());
+    }
+
+    #[test]
+    fn serializes_to_json() {
+        let pr = parse_output("fix: typo\n\nBody");
+        let json = serde_json::to_string(&pr).unwrap();
+        assert_eq!(json, r#"{"title":"fix: typo","body":"Body"}"#);
+    }
+}
@@ -1,44 +1,248 @@
 use crate::config::Config;
+use crate::format::{self, Rules};
+use std::collections::BTreeMap;
+
+/// Repository-specific context that shapes the system prompt
+#[derive(Debug, Default)]
+pub struct RepoContext {
+    /// Recent commit messages used as style examples
+    pub examples: Vec<String>,
+    /// commit.template contents with comment lines removed
+    pub template: Option<String>,
+    /// Scopes recent commits used, most frequent first (commit.scopes = "infer")
+    pub scopes: Vec<String>,
+}
+
+/// Recent commits scanned for scopes when commit.scopes = "infer"
+pub const SCOPE_HISTORY: usize = 200;
+/// Most inferred scopes listed in the prompt
+pub const INFERRED_SCOPES: usize = 20;
+
+/// Scopes used in commit subjects, most frequent first
+pub fn common_scopes(subjects: &[String], limit: usize) -> Vec<String> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for subject in subjects {
+        if let Some(scope) =
+            format::parse_header(format::strip_gitmoji(subject)).and_then(|h| h.scope)
+        {
+            *counts.entry(scope).or_default() += 1;
+        }
+    }
+    let mut scopes: Vec<(&str, usize)> = counts.into_iter().collect();
+    // Stable sort keeps ties in alphabetical order
+    scopes.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    scopes
+        .into_iter()
+        .take(limit)
+        .map(|(s, _)| s.to_string())
+        .collect()
+}
 
-pub fn build_system_prompt(config: &Config) -> String {
+pub fn build_system_prompt(config: &Config, repo: &RepoContext) -> String {
     let mut parts = Vec::new();
 
     parts.push(
         "You are a commit message generator. Given a git diff, generate a concise, \
          accurate commit message. Output ONLY the commit message, nothing else. \
-         No markdown formatting, no code blocks, no quotes."
+         No markdown formatting, no code blocks, no quotes. \
+         Never add trailers such as Signed-off-by or Co-authored-by; they are added separately."
             .to_string(),
     );
 
-    if config.commit.conventional {
-        parts.push(
+    let rules = Rules::from_config(config);
+    if rules.conventional {
+        let types = rules
+            .types
+            .iter()
+            .map(|(name, kind)| format!("- {name}: {}", kind.description))
+            .collect::<Vec<_>>()
+            .join("\n");
+        parts.push(format!(
             "Use Conventional Commits format: <type>(<optional scope>): <description>\n\n\
-             Valid types: feat, fix, docs, style, refactor, perf, test, build, ci, chore, revert.\n\
-             The first line must be under 72 characters.\n\
-             If the change is significant, add a blank line followed by a body explaining what and why."
-                .to_string(),
-        );
+             Valid types:\n{types}"
+        ));
+        if !rules.scopes.is_empty() {
+            parts.push(format!(
+                "If you use a scope, it must be one of: {}.",
+                rules.scopes.join(", ")
+            ));
+        } else if config.commit.scopes.infer() {
+            parts.push(if repo.scopes.is_empty() {
+                "Add a scope naming the module or area the change touches, as one short lowercase word."
+                    .to_string()
+            } else {
+                format!(
+                    "Add a scope naming the area the change touches. \
+                     Reuse one of the scopes this repository already uses when it fits: {}.",
+                    repo.scopes.join(", ")
+                )
+            });
+        }
+    }
+    parts.push(format!(
+        "The first line must be at most {} characters.\n\
+         If the change is significant, add a blank line followed by a body explaining what and why. \
+         Wrap body lines at {} characters.",
+        rules.max_subject_length, rules.body_wrap
+    ));
+
+    let gitmoji: Vec<String> = rules
+        .types
+        .iter()
+        .filter(|(_, kind)| !kind.emoji.is_empty())
+        .map(|(name, kind)| format!("- {name}: {}", kind.emoji))
+        .collect();
+    if config.commit.emoji && !gitmoji.is_empty() {
+        parts.push(format!(
+            "Prefix the commit type with its Gitmoji:\n{}",
+            gitmoji.join("\n")
+        ));
+    }
+
+    parts.extend(language_instruction(
+        &config.commit.language,
+        "the commit message",
+    ));
+
+    if let Some(template) = &repo.template {
+        parts.push(format!(
+            "This repository requires the following commit message template. \
+             Follow its structure exactly and fill in every section, \
+             including any checklist items:\n{template}"
+        ));
+    }
+
+    if !repo.examples.is_empty() {
+        let list = repo
+            .examples
+            .iter()
+            .map(|e| format!("---\n{e}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        parts.push(format!(
+            "Recent commit messages from this repository are shown below. \
+             Match their style and conventions (scopes, tense, ticket prefixes), \
+             but describe only the current diff:\n{list}\n---"
+        ));
+    }
+
+    if !config.prompt.system.is_empty() {
+        parts.push(config.prompt.system.clone());
     }
 
-    if config.commit.emoji {
+    parts.join("\n\n")
+}
+
+/// "Write <what> in <language>." for non-English languages
+fn language_instruction(language: &str, what: &str) -> Option<String> {
+    match language {
+        "en" => None, // default
+        "ja" => Some(format!("Write {what} in Japanese.")),
+        lang => Some(format!("Write {what} in {lang}.")),
+    }
+}
+
+pub fn build_pr_system_prompt(config: &Config) -> String {
+    let mut parts = Vec::new();
+
+    parts.push(
+        "You are a pull request description writer. Given a branch's commit log and diff, \
+         write a pull request title and description. Output the title on the first line, \
+         then a blank line, then the description in Markdown. \
+         Do not wrap the output in a code block."
+            .to_string(),
+    );
+
+    if config.commit.conventional {
         parts.push(
-            "Prefix the commit type with an appropriate Gitmoji:\n\
-             - feat: ✨  - fix: 🐛  - docs: 📝  - style: 💄  - refactor: ♻️\n\
-             - perf: ⚡  - test: ✅  - build: 📦  - ci: 👷  - chore: 🔧  - revert: ⏪"
+            "Write the title in Conventional Commits format: <type>(<optional scope>): <description>. \
+             Keep it under 72 characters."
                 .to_string(),
         );
     }
 
-    match config.commit.language.as_str() {
-        "ja" => parts.push("Write the commit message in Japanese.".to_string()),
-        "en" => {} // default
-        lang => parts.push(format!("Write the commit message in {lang}.")),
+    parts.extend(language_instruction(
+        &config.commit.language,
+        "the title and description",
+    ));
+
+    if !config.prompt.system.is_empty() {
+        parts.push(config.prompt.system.clone());
+    }
+
+    parts.join("\n\n")
+}
+
+pub fn build_pr_prompt(
+    commit_log: &str,
+    diff: &str,
+    pr_template: Option<&str>,
+    hint: Option<&str>,
+    max_diff_length: usize,
+) -> String {
+    let mut parts = Vec::new();
+
+    if let Some(h) = hint {
+        parts.push(format!("Context: {h}"));
+    }
+
+    match pr_template {
+        Some(t) => parts.push(format!(
+            "The repository has a pull request template. Use it as the description, \
+             keeping its headings and filling in every section:\n{t}"
+        )),
+        None => parts.push(
+            "Structure the description with these sections: \
+             ## Summary, ## Changes, ## Testing."
+                .to_string(),
+        ),
     }
 
+    parts.push(format!("Commits on this branch:\n{commit_log}"));
+
+    let truncated_diff = truncate_diff(diff, max_diff_length);
+    parts.push(format!("Branch diff:\n```diff\n{truncated_diff}\n```"));
+
+    parts.join("\n\n")
+}
+
+pub fn build_changelog_system_prompt(config: &Config) -> String {
+    let mut parts = vec![
+        "You rewrite commit summaries into short, user-facing changelog entries. \
+         Output exactly one line per input entry, in the same order, \
+         each starting with \"- \". Do not add, merge or drop entries."
+            .to_string(),
+    ];
+    parts.extend(language_instruction(&config.commit.language, "the entries"));
+    parts.join("\n\n")
+}
+
+pub fn build_changelog_prompt(entries: &[String]) -> String {
+    format!("Entries:\n{}", entries.join("\n"))
+}
+
+pub fn build_release_system_prompt(config: &Config) -> String {
+    let mut parts = vec![
+        "You write annotated git tag messages for releases. Given a version and its changelog, \
+         write a short paragraph summarizing the release for users, followed by a blank line \
+         and the most important changes as a plain \"- \" list. \
+         Output ONLY the message body, with no title, no markdown headings and no code blocks."
+            .to_string(),
+    ];
+    parts.extend(language_instruction(&config.commit.language, "the message"));
     if !config.prompt.system.is_empty() {
         parts.push(config.prompt.system.clone());
     }
+    parts.join("\n\n")
+}
 
+pub fn build_release_prompt(version: &str, changelog: &str, hint: Option<&str>) -> String {
+    let mut parts = Vec::new();
+    if let Some(h) = hint {
+        parts.push(format!("Context: {h}"));
+    }
+    parts.push(format!("Version: {version}"));
+    parts.push(format!("Changelog:\n{changelog}"));
     parts.join("\n\n")
 }
 
@@ -95,6 +299,115 @@ pub fn build_edit_prompt(
     parts.join("\n\n")
 }
 
+pub fn build_repair_prompt(message: &str, problems: &[String]) -> String {
+    format!(
+        "This commit message breaks the repository's rules:\n{}\n\n\
+         Message:\n{message}\n\n\
+         Rewrite it so it follows the rules while preserving the author's intent and wording \
+         as much as possible. Keep any trailer lines (such as Signed-off-by) unchanged. \
+         Output ONLY the commit message.",
+        problems
+            .iter()
+            .map(|p| format!("- {p}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    )
+}
+
+pub fn build_merge_prompt(
+    subject: &str,
+    commits: &[String],
+    conflicts: &[String],
+    diff: &str,
+    hint: Option<&str>,
+    max_diff_length: usize,
+) -> String {
+    let mut parts = Vec::new();
+
+    parts.push(format!(
+        "This is a merge commit. Use this exact first line, ignoring any other format rules:\n{subject}\n\n\
+         In the body, summarize what the incoming commits below bring in, grouped by theme. \
+         Do not list every commit verbatim."
+    ));
+
+    if let Some(h) = hint {
+        parts.push(format!("Context: {h}"));
+    }
+
+    if !commits.is_empty() {
+        parts.push(format!("Incoming commits:\n{}", commits.join("\n")));
+    }
+
+    if !conflicts.is_empty() {
+        parts.push(format!(
+            "These files had conflicts that were resolved in this merge:\n{}\n\n\
+             End the body with a short \"Conflict resolution:\" section explaining, \
+             based on the diff, how each conflict was resolved.",
+            conflicts.join("\n")
+        ));
+    }
+
+    let truncated_diff = truncate_diff(diff, max_diff_length);
+    parts.push(format!("Merge diff:\n```diff\n{truncated_diff}\n```"));
+
+    parts.join("\n\n")
+}
+
+pub fn build_squash_prompt(
+    commit_log: &str,
+    diff: &str,
+    hint: Option<&str>,
+    max_diff_length: usize,
+) -> String {
+    let mut parts = Vec::new();
+
+    parts.push(
+        "Several commits are being squashed into one. Write a single cohesive commit message \
+         describing the combined change. Do not list the original commits one by one."
+            .to_string(),
+    );
+
+    if let Some(h) = hint {
+        parts.push(format!("Context: {h}"));
+    }
+
+    parts.push(format!("Original commit messages:\n{commit_log}"));
+
+    let truncated_diff = truncate_diff(diff, max_diff_length);
+    parts.push(format!("Combined diff:\n```diff\n{truncated_diff}\n```"));
+
+    parts.join("\n\n")
+}
+
+pub fn build_revert_reason_prompt(
+    reverted_message: &str,
+    diff: &str,
+    hint: &str,
+    max_diff_length: usize,
+) -> String {
+    let truncated_diff = truncate_diff(diff, max_diff_length);
+    format!(
+        "A commit is being reverted. Its message was:\n{reverted_message}\n\n\
+         Reason given by the developer: {hint}\n\n\
+         Revert diff:\n```diff\n{truncated_diff}\n```\n\n\
+         Write one short paragraph explaining why the commit is reverted. \
+         Output ONLY the paragraph, with no subject line."
+    )
+}
+
+/// Context for a commit replayed during a rebase
+pub fn rebase_hint(original_message: &str, hint: Option<&str>) -> String {
+    let mut text = format!(
+        "This commit is being replayed during a rebase. Its original message was:\n\
+         {original_message}\n\
+         Keep that message unless the diff shows it is no longer accurate."
+    );
+    if let Some(h) = hint {
+        text.push_str(&format!("\n{h}"));
+    }
+    text
+}
+
 pub fn truncate_diff(diff: &str, max_length: usize) -> String {
     if diff.len() <= max_length {
         return diff.to_string();
@@ -133,7 +446,7 @@ pub fn truncate_diff(diff: &str, max_length: usize) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::Config;
+    use crate::config::{CommitType, Config, Infer, Scopes};
 
     fn default_config() -> Config {
         Config::default()
@@ -142,23 +455,89 @@ mod tests {
     #[test]
     fn system_prompt_includes_conventional() {
         let config = default_config();
-        let prompt = build_system_prompt(&config);
+        let prompt = build_system_prompt(&config, &RepoContext::default());
         assert!(prompt.contains("Conventional Commits"));
     }
 
+    #[test]
+    fn system_prompt_forbids_trailers() {
+        let prompt = build_system_prompt(&default_config(), &RepoContext::default());
+        assert!(prompt.contains("Never add trailers"));
+    }
+
     #[test]
     fn system_prompt_excludes_conventional_when_disabled() {
         let mut config = default_config();
         config.commit.conventional = false;
-        let prompt = build_system_prompt(&config);
+        let prompt = build_system_prompt(&config, &RepoContext::default());
         assert!(!prompt.contains("Conventional Commits"));
     }
 
+    #[test]
+    fn system_prompt_uses_shared_rules() {
+        let mut config = default_config();
+        config.commit.max_subject_length = 50;
+        config.commit.scopes = Scopes::List(vec!["api".to_string(), "ui".to_string()]);
+        let prompt = build_system_prompt(&config, &RepoContext::default());
+        assert!(prompt.contains("at most 50 characters"));
+        assert!(prompt.contains("must be one of: api, ui"));
+        assert!(prompt.contains("Valid types:\n- feat: A new feature\n- fix: A bug fix\n"));
+    }
+
+    #[test]
+    fn system_prompt_uses_configured_types() {
+        let mut config = default_config();
+        config.commit.emoji = true;
+        for (name, emoji) in [("security", "🔒"), ("deps", "⬆️"), ("i18n", "")] {
+            let kind = CommitType {
+                description: format!("{name} changes"),
+                emoji: emoji.to_string(),
+            };
+            config.commit.types.insert(name.to_string(), kind);
+        }
+        let prompt = build_system_prompt(&config, &RepoContext::default());
+        assert!(prompt.contains(
+            "Valid types:\n- security: security changes\n- deps: deps changes\n- i18n: i18n changes"
+        ));
+        assert!(!prompt.contains("- feat"));
+        assert!(prompt.ends_with("Gitmoji:\n- security: 🔒\n- deps: ⬆️"));
+    }
+
+    #[test]
+    fn system_prompt_suggests_inferred_scopes() {
+        let mut config = default_config();
+        config.commit.scopes = Scopes::Infer(Infer::Infer);
+        let repo = RepoContext {
+            scopes: vec!["cli".to_string(), "config".to_string()],
+            ..RepoContext::default()
+        };
+        let prompt = build_system_prompt(&config, &repo);
+        assert!(prompt.contains("already uses when it fits: cli, config."));
+        let prompt = build_system_prompt(&config, &RepoContext::default());
+        assert!(prompt.contains("Add a scope naming the module or area"));
+    }
+
+    #[test]
+    fn common_scopes_orders_by_frequency() {
+        let subjects: Vec<String> = [
+            "feat(config): a",
+            "fix(cli): b",
+            "✨ feat(config): c",
+            "docs: d",
+            "fix(api)!: e",
+            "Merge branch 'x'",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        assert_eq!(common_scopes(&subjects, 2), vec!["config", "api"]);
+    }
+
     #[test]
     fn system_prompt_includes_emoji_when_enabled() {
         let mut config = default_config();
         config.commit.emoji = true;
-        let prompt = build_system_prompt(&config);
+        let prompt = build_system_prompt(&config, &RepoContext::default());
         assert!(prompt.contains("Gitmoji"));
     }
 
@@ -166,7 +545,7 @@ mod tests {
     fn system_prompt_japanese() {
         let mut config = default_config();
         config.commit.language = "ja".to_string();
-        let prompt = build_system_prompt(&config);
+        let prompt = build_system_prompt(&config, &RepoContext::default());
         assert!(prompt.contains("Japanese"));
     }
 
@@ -174,10 +553,45 @@ mod tests {
     fn system_prompt_custom_system() {
         let mut config = default_config();
         config.prompt.system = "Always mention the ticket number.".to_string();
-        let prompt = build_system_prompt(&config);
+        let prompt = build_system_prompt(&config, &RepoContext::default());
         assert!(prompt.contains("Always mention the ticket number."));
     }
 
+    #[test]
+    fn system_prompt_includes_history_examples() {
+        let config = default_config();
+        let examples = vec![
+            "feat(api): PROJ-12 add rate limiting".to_string(),
+            "fix(ui): PROJ-9 align header".to_string(),
+        ];
+        let repo = RepoContext {
+            examples,
+            ..Default::default()
+        };
+        let prompt = build_system_prompt(&config, &repo);
+        assert!(prompt.contains("Recent commit messages"));
+        assert!(prompt.contains("PROJ-12 add rate limiting"));
+        assert!(prompt.contains("PROJ-9 align header"));
+    }
+
+    #[test]
+    fn system_prompt_includes_template() {
+        let repo = RepoContext {
+            template: Some("Summary:\n\nChecklist:\n- [ ] tests".to_string()),
+            ..Default::default()
+        };
+        let prompt = build_system_prompt(&default_config(), &repo);
+        assert!(prompt.contains("commit message template"));
+        assert!(prompt.contains("- [ ] tests"));
+    }
+
+    #[test]
+    fn system_prompt_omits_history_when_empty() {
+        let config = default_config();
+        let prompt = build_system_prompt(&config, &RepoContext::default());
+        assert!(!prompt.contains("Recent commit messages"));
+    }
+
     #[test]
     fn user_prompt_includes_diff() {
         let prompt = build_user_prompt("+ added line", "", None, 8000);
@@ -196,6 +610,73 @@ mod tests {
         assert!(prompt.contains("M src/main.rs"));
     }
 
+    #[test]
+    fn merge_prompt_includes_subject_commits_and_conflicts() {
+        let prompt = build_merge_prompt(
+            "Merge branch 'feature'",
+            &["feat: add login".to_string()],
+            &["src/auth.rs".to_string()],
+            "diff",
+            None,
+            8000,
+        );
+        assert!(prompt.contains("Merge branch 'feature'"));
+        assert!(prompt.contains("feat: add login"));
+        assert!(prompt.contains("src/auth.rs"));
+        assert!(prompt.contains("Conflict resolution"));
+    }
+
+    #[test]
+    fn merge_prompt_omits_conflicts_when_clean() {
+        let prompt = build_merge_prompt("Merge branch 'x'", &[], &[], "diff", None, 8000);
+        assert!(!prompt.contains("Conflict resolution"));
+    }
+
+    #[test]
+    fn squash_prompt_includes_log_and_diff() {
+        let prompt = build_squash_prompt("feat: a\n\n---\n\nfix: b", "+ line", None, 8000);
+        assert!(prompt.contains("single cohesive"));
+        assert!(prompt.contains("fix: b"));
+        assert!(prompt.contains("+ line"));
+    }
+
+    #[test]
+    fn pr_prompt_uses_template_when_present() {
+        let prompt = build_pr_prompt("feat: a", "diff", Some("## Checklist"), None, 8000);
+        assert!(prompt.contains("## Checklist"));
+        assert!(!prompt.contains("## Summary"));
+        let prompt = build_pr_prompt("feat: a", "diff", None, None, 8000);
+        assert!(prompt.contains("## Summary"));
+    }
+
+    #[test]
+    fn pr_system_prompt_honors_language() {
+        let mut config = default_config();
+        config.commit.language = "ja".to_string();
+        assert!(build_pr_system_prompt(&config).contains("Japanese"));
+    }
+
+    #[test]
+    fn release_prompt_includes_version_and_changelog() {
+        let prompt = build_release_prompt("v1.2.0", "### Added\n- login", None);
+        assert!(prompt.contains("v1.2.0"));
+        assert!(prompt.contains("- login"));
+    }
+
+    #[test]
+    fn repair_prompt_lists_problems() {
+        let prompt = build_repair_prompt("Add login", &["Type 'x' is invalid".to_string()]);
+        assert!(prompt.contains("- Type 'x' is invalid"));
+        assert!(prompt.contains("Add login"));
+    }
+
+    #[test]
+    fn revert_prompt_includes_reason() {
+        let prompt = build_revert_reason_prompt("feat: add cache", "diff", "causes OOM", 8000);
+        assert!(prompt.contains("feat: add cache"));
+        assert!(prompt.contains("causes OOM"));
+    }
+
     #[test]
     fn truncate_diff_short() {
         let diff = "short diff";
@@ -11,7 +11,7 @@ impl ApiProvider {
     pub fn new(api_key: &str) -> Result<Self> {
         if api_key.is_empty() {
             bail!(
-                "API key is required for 'api' provider. Set auth.api_key in config or use ANTHROPIC_API_KEY env var."
+                "API key is required for 'api' provider. Run `ccmt auth login`, set auth.api_key_command, or use ANTHROPIC_API_KEY env var."
             );
         }
         Ok(Self {
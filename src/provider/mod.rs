@@ -1,6 +1,7 @@
 pub mod api;
 pub mod claude_cli;
 
+use crate::config::Config;
 use anyhow::Result;
 
 pub trait Provider {
@@ -14,3 +15,12 @@ pub fn create_provider(name: &str, api_key: &str) -> Result<Box<dyn Provider>> {
         other => anyhow::bail!("Unknown provider: {other}. Use 'cli' or 'api'."),
     }
 }
+
+/// Create the configured provider, resolving the API key only when it is needed
+pub fn from_config(config: &Config) -> Result<Box<dyn Provider>> {
+    let api_key = match config.auth.provider.as_str() {
+        "api" => crate::auth::api_key(config)?,
+        _ => String::new(),
+    };
+    create_provider(&config.auth.provider, &api_key)
+}
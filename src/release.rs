@@ -0,0 +1,220 @@
+use crate::changelog::{self, Entry};
+use crate::config::Config;
+use crate::{git, prompt, provider};
+use anyhow::{Result, bail};
+use colored::Colorize;
+use dialoguer::Confirm;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl fmt::Display for Bump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Bump::Patch => "patch",
+            Bump::Minor => "minor",
+            Bump::Major => "major",
+        };
+        f.write_str(s)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    /// Parse `1.2.3` or `v1.2.3`; pre-release and build suffixes are rejected
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.strip_prefix('v').unwrap_or(s);
+        let mut parts = s.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        let patch = parts.next()?.parse().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            major,
+            minor,
+            patch,
+        })
+    }
+
+    pub fn bump(self, bump: Bump) -> Self {
+        match bump {
+            Bump::Major => Self {
+                major: self.major + 1,
+                minor: 0,
+                patch: 0,
+            },
+            Bump::Minor => Self {
+                minor: self.minor + 1,
+                patch: 0,
+                ..self
+            },
+            Bump::Patch => Self {
+                patch: self.patch + 1,
+                ..self
+            },
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Major for breaking changes, minor for features, patch otherwise
+pub fn bump_for(entries: &[Entry]) -> Bump {
+    entries
+        .iter()
+        .map(|e| {
+            if e.breaking {
+                Bump::Major
+            } else if e.kind == "feat" {
+                Bump::Minor
+            } else {
+                Bump::Patch
+            }
+        })
+        .max()
+        .unwrap_or(Bump::Patch)
+}
+
+/// Most recent semver tag reachable from HEAD, with its parsed version
+fn latest_release() -> Option<(String, Version)> {
+    git::git_tags_merged()
+        .ok()?
+        .into_iter()
+        .filter_map(|t| Version::parse(&t).map(|v| (t, v)))
+        .max_by_key(|(_, v)| *v)
+}
+
+pub fn run(
+    config: &Config,
+    create_tag: bool,
+    confirm: bool,
+    sign: Option<Option<&str>>,
+    hint: Option<&str>,
+) -> Result<()> {
+    let latest = latest_release();
+    let (range, previous, prefix) = match &latest {
+        Some((tag, v)) => {
+            let prefix = if tag.starts_with('v') { "v" } else { "" };
+            (format!("{tag}..HEAD"), *v, prefix)
+        }
+        None => ("HEAD".to_string(), Version::default(), "v"),
+    };
+
+    let commits = git::git_log_commits(&range)?;
+    if commits.is_empty() {
+        bail!(
+            "No commits since {}.",
+            latest.map(|(t, _)| t).unwrap_or_default()
+        );
+    }
+    let entries: Vec<Entry> = commits
+        .iter()
+        .filter_map(|(hash, msg)| changelog::parse_commit(hash, msg))
+        .collect();
+
+    let bump = bump_for(&entries);
+    let next = previous.bump(bump);
+    let tag = format!("{prefix}{next}");
+
+    eprintln!(
+        "{} {tag} ({bump} bump, {} commits since {})",
+        "Next version:".bold(),
+        commits.len(),
+        latest
+            .as_ref()
+            .map_or("the first commit", |(t, _)| t.as_str()),
+    );
+
+    let date = git::git_commit_date("HEAD")?;
+    let notes = changelog::render(
+        &changelog::visible_entries(&entries),
+        Some(&next.to_string()),
+        &date,
+    );
+
+    let provider = provider::from_config(config)?;
+    eprintln!("{}", "Generating tag message...".dimmed());
+    let body = provider.generate(
+        &prompt::build_release_prompt(&tag, &notes, hint),
+        &prompt::build_release_system_prompt(config),
+        &config.auth.model,
+    )?;
+    let message = format!("Release {tag}\n\n{}", body.trim());
+
+    println!("{message}");
+
+    if !create_tag {
+        return Ok(());
+    }
+    if confirm
+        && !Confirm::new()
+            .with_prompt(format!("Create annotated tag {tag}?"))
+            .default(true)
+            .interact()?
+    {
+        eprintln!("{}", "Cancelled.".yellow());
+        return Ok(());
+    }
+
+    git::git_tag_annotated(&tag, &message, sign)?;
+    eprintln!("{} {tag}", "Tagged:".green().bold());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(msgs: &[&str]) -> Vec<Entry> {
+        msgs.iter()
+            .filter_map(|m| changelog::parse_commit("abc1234", m))
+            .collect()
+    }
+
+    #[test]
+    fn parses_versions() {
+        let v = Version::parse("v1.2.3").unwrap();
+        assert_eq!((v.major, v.minor, v.patch), (1, 2, 3));
+        assert_eq!(Version::parse("1.2.3").unwrap(), v);
+        assert!(Version::parse("1.2").is_none());
+        assert!(Version::parse("1.2.3-rc.1").is_none());
+        assert!(Version::parse("release").is_none());
+    }
+
+    #[test]
+    fn bumps_versions() {
+        let v = Version::parse("1.2.3").unwrap();
+        assert_eq!(v.bump(Bump::Major).to_string(), "2.0.0");
+        assert_eq!(v.bump(Bump::Minor).to_string(), "1.3.0");
+        assert_eq!(v.bump(Bump::Patch).to_string(), "1.2.4");
+    }
+
+    #[test]
+    fn bump_from_commits() {
+        assert_eq!(bump_for(&entries(&["fix: a", "docs: b"])), Bump::Patch);
+        assert_eq!(bump_for(&entries(&["fix: a", "feat: b"])), Bump::Minor);
+        assert_eq!(bump_for(&entries(&["feat: a", "fix!: b"])), Bump::Major);
+        assert_eq!(
+            bump_for(&entries(&["chore: a\n\nBREAKING CHANGE: x"])),
+            Bump::Major
+        );
+        assert_eq!(bump_for(&[]), Bump::Patch);
+    }
+}
@@ -0,0 +1,237 @@
+use crate::format;
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+
+/// Extract ticket IDs from a branch name, in order of appearance, without duplicates
+pub fn extract_tickets(branch: &str, patterns: &[String]) -> Result<Vec<String>> {
+    let mut tickets: Vec<String> = Vec::new();
+    for pattern in patterns {
+        let re =
+            Regex::new(pattern).with_context(|| format!("Invalid ticket pattern: {pattern}"))?;
+        for m in re.find_iter(branch) {
+            let id = m.as_str().to_string();
+            if !tickets.contains(&id) {
+                tickets.push(id);
+            }
+        }
+    }
+    Ok(tickets)
+}
+
+/// Where ticket IDs go in the message (commit.ticket_position)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Position {
+    None,
+    /// Start of the description
+    Prefix,
+    Scope,
+    /// `Refs:` trailer
+    Footer,
+}
+
+impl Position {
+    /// Parse commit.ticket_position; checked before anything is generated
+    pub fn parse(value: &str) -> Result<Self> {
+        Ok(match value {
+            "none" => Position::None,
+            "prefix" => Position::Prefix,
+            "scope" => Position::Scope,
+            "footer" => Position::Footer,
+            other => {
+                bail!(
+                    "Unknown ticket_position: {other}. Use 'prefix', 'scope', 'footer' or 'none'."
+                )
+            }
+        })
+    }
+}
+
+/// Insert ticket IDs into the message according to `position`
+///
+/// IDs go in the `Refs:` trailer instead when the subject can't take them: a scope
+/// list is configured (the ID would fail scope-enum) or the subject would get too long.
+pub fn apply_tickets(
+    msg: &str,
+    tickets: &[String],
+    position: Position,
+    rules: &format::Rules,
+) -> String {
+    let missing: Vec<&str> = tickets
+        .iter()
+        .map(String::as_str)
+        .filter(|t| !msg.contains(t))
+        .collect();
+    if missing.is_empty() || position == Position::None {
+        return msg.to_string();
+    }
+    let ids = missing.join(", ");
+    let footer = || format::append_trailer(msg, "Refs", &ids);
+
+    let (subject, rest) = match msg.split_once('\n') {
+        Some((s, r)) => (s, Some(r)),
+        None => (msg, None),
+    };
+    // A leading Gitmoji stays in front of the header
+    let bare = format::strip_gitmoji(subject);
+    let emoji = &subject[..subject.len() - bare.len()];
+    let header = format::parse_header(bare);
+
+    let new_subject = match position {
+        Position::None => return msg.to_string(),
+        Position::Footer => return footer(),
+        Position::Prefix => match header {
+            Some(h) => {
+                let description = format!("{} {}", missing.join(" "), h.description);
+                format::Header {
+                    description: &description,
+                    ..h
+                }
+                .render()
+            }
+            None => format!("{} {bare}", missing.join(" ")),
+        },
+        Position::Scope => match header {
+            Some(_) if !rules.scopes.is_empty() => return footer(),
+            Some(h) => {
+                let scope = match h.scope {
+                    Some(s) => format!("{s},{}", missing.join(",")),
+                    None => missing.join(","),
+                };
+                format::Header {
+                    scope: Some(&scope),
+                    ..h
+                }
+                .render()
+            }
+            None => format!("{} {bare}", missing.join(" ")),
+        },
+    };
+    let new_subject = format!("{emoji}{new_subject}");
+    if new_subject.chars().count() > rules.max_subject_length {
+        return footer();
+    }
+
+    match rest {
+        Some(r) => format!("{new_subject}\n{r}"),
+        None => new_subject,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply_tickets_with(msg: &str, tickets: &[String], position: Position) -> String {
+        apply_tickets(msg, tickets, position, &format::Rules::default())
+    }
+
+    fn default_patterns() -> Vec<String> {
+        vec![r"[A-Z][A-Z0-9]+-\d+".to_string(), r"#\d+".to_string()]
+    }
+
+    #[test]
+    fn extracts_jira_key() {
+        let t = extract_tickets("feature/PROJ-123-add-login", &default_patterns()).unwrap();
+        assert_eq!(t, vec!["PROJ-123"]);
+    }
+
+    #[test]
+    fn extracts_multiple_without_duplicates() {
+        let t = extract_tickets("fix/ABC-1-ABC-1-#42", &default_patterns()).unwrap();
+        assert_eq!(t, vec!["ABC-1", "#42"]);
+    }
+
+    #[test]
+    fn no_match_on_plain_branch() {
+        assert!(
+            extract_tickets("main", &default_patterns())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn invalid_pattern_errors() {
+        assert!(extract_tickets("main", &["[".to_string()]).is_err());
+    }
+
+    #[test]
+    fn prefix_inserts_after_type() {
+        let msg = apply_tickets_with(
+            "feat(auth): add login",
+            &["PROJ-1".into()],
+            Position::Prefix,
+        );
+        assert_eq!(msg, "feat(auth): PROJ-1 add login");
+    }
+
+    #[test]
+    fn prefix_on_plain_subject() {
+        let msg = apply_tickets_with("Add login\n\nBody", &["PROJ-1".into()], Position::Prefix);
+        assert_eq!(msg, "PROJ-1 Add login\n\nBody");
+    }
+
+    #[test]
+    fn scope_replaces_or_extends_scope() {
+        let msg = apply_tickets_with("feat: add login", &["PROJ-1".into()], Position::Scope);
+        assert_eq!(msg, "feat(PROJ-1): add login");
+        let msg = apply_tickets_with("feat(auth): add login", &["PROJ-1".into()], Position::Scope);
+        assert_eq!(msg, "feat(auth,PROJ-1): add login");
+    }
+
+    #[test]
+    fn gitmoji_stays_in_front() {
+        let msg = apply_tickets_with("✨ feat: add login", &["PROJ-1".into()], Position::Scope);
+        assert_eq!(msg, "✨ feat(PROJ-1): add login");
+        let msg = apply_tickets_with("✨ feat: add login", &["PROJ-1".into()], Position::Prefix);
+        assert_eq!(msg, "✨ feat: PROJ-1 add login");
+    }
+
+    #[test]
+    fn falls_back_to_refs_when_the_subject_cannot_take_it() {
+        let rules = format::Rules {
+            scopes: vec!["auth".to_string()],
+            ..format::Rules::default()
+        };
+        let msg = apply_tickets(
+            "feat(auth): add login",
+            &["PROJ-1".into()],
+            Position::Scope,
+            &rules,
+        );
+        assert_eq!(msg, "feat(auth): add login\n\nRefs: PROJ-1");
+        assert!(format::lint(&msg, &rules).is_empty());
+
+        let subject = format!("feat: {}", "x".repeat(64));
+        let msg = apply_tickets_with(&subject, &["PROJ-1".into()], Position::Prefix);
+        assert_eq!(msg, format!("{subject}\n\nRefs: PROJ-1"));
+    }
+
+    #[test]
+    fn footer_appends_refs() {
+        let msg = apply_tickets_with("feat: add login", &["PROJ-1".into()], Position::Footer);
+        assert_eq!(msg, "feat: add login\n\nRefs: PROJ-1");
+    }
+
+    #[test]
+    fn skips_tickets_already_present() {
+        let msg = apply_tickets_with(
+            "feat: PROJ-1 add login",
+            &["PROJ-1".into()],
+            Position::Footer,
+        );
+        assert_eq!(msg, "feat: PROJ-1 add login");
+    }
+
+    #[test]
+    fn positions_are_parsed_up_front() {
+        assert_eq!(Position::parse("footer").unwrap(), Position::Footer);
+        assert!(Position::parse("top").is_err());
+    }
+
+    #[test]
+    fn none_leaves_message_untouched() {
+        let msg = apply_tickets_with("feat: add login", &["PROJ-1".into()], Position::None);
+        assert_eq!(msg, "feat: add login");
+    }
+}
@@ -0,0 +1,56 @@
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn git(dir: &Path, home: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .current_dir(dir)
+        .env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .args(args)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
+fn ccmt(dir: &Path, home: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ccmt"))
+        .current_dir(dir)
+        .env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn repository_installs_leave_the_global_hook_alone() {
+    let tmp = tempfile::tempdir().unwrap();
+    let home = tmp.path();
+    let repo = home.join("repo");
+    std::fs::create_dir(&repo).unwrap();
+    git(&repo, home, &["init", "-q"]);
+
+    assert!(
+        ccmt(&repo, home, &["hook", "install", "--global"])
+            .status
+            .success()
+    );
+    let global = home.join("config/ccmt/hooks/prepare-commit-msg");
+    let script = std::fs::read_to_string(&global).unwrap();
+    assert!(script.contains("--git-common-dir)/hooks/prepare-commit-msg"));
+
+    for args in [&["hook", "install"][..], &["hook", "remove"][..]] {
+        let output = ccmt(&repo, home, args);
+        assert!(!output.status.success(), "{args:?} should refuse");
+        assert!(String::from_utf8_lossy(&output.stderr).contains("--global"));
+        assert_eq!(std::fs::read_to_string(&global).unwrap(), script);
+    }
+
+    // A repository that sets its own core.hooksPath can still install there
+    git(&repo, home, &["config", "core.hooksPath", ".githooks"]);
+    assert!(ccmt(&repo, home, &["hook", "install"]).status.success());
+    assert!(repo.join(".githooks/prepare-commit-msg").is_file());
+    assert!(ccmt(&repo, home, &["hook", "remove"]).status.success());
+    assert_eq!(std::fs::read_to_string(&global).unwrap(), script);
+}
@@ -0,0 +1,51 @@
+use std::path::Path;
+use std::process::Command;
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
+fn lint(dir: &Path, home: &Path, args: &[&str]) -> Option<i32> {
+    Command::new(env!("CARGO_BIN_EXE_ccmt"))
+        .current_dir(dir)
+        .env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .arg("lint")
+        .args(args)
+        .output()
+        .unwrap()
+        .status
+        .code()
+}
+
+#[test]
+fn lint_exit_codes_separate_violations_from_errors() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path().join("repo");
+    std::fs::create_dir(&repo).unwrap();
+    git(&repo, &["init", "-q"]);
+    git(&repo, &["config", "user.name", "Test"]);
+    git(&repo, &["config", "user.email", "test@example.com"]);
+    git(&repo, &["config", "commit.gpgsign", "false"]);
+    git(
+        &repo,
+        &["commit", "-q", "--allow-empty", "-m", "feat: add login"],
+    );
+
+    assert_eq!(lint(&repo, tmp.path(), &[]), Some(0));
+
+    std::fs::write(tmp.path().join("bad"), "Add login\n").unwrap();
+    let bad = tmp.path().join("bad");
+    assert_eq!(
+        lint(&repo, tmp.path(), &["--file", bad.to_str().unwrap()]),
+        Some(1)
+    );
+
+    assert_eq!(lint(&repo, tmp.path(), &["nope..HEAD"]), Some(2));
+    assert_eq!(lint(&repo, tmp.path(), &["--file", "missing"]), Some(2));
+}
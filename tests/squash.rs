@@ -0,0 +1,78 @@
+use std::path::Path;
+use std::process::Command;
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {args:?} failed");
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn commit_file(dir: &Path, name: &str, content: &str, message: &str) {
+    std::fs::write(dir.join(name), content).unwrap();
+    git(dir, &["add", name]);
+    git(dir, &["commit", "-q", "-m", message]);
+}
+
+#[test]
+fn squash_onto_a_diverged_base_keeps_its_commits() {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = tmp.path().join("repo");
+    std::fs::create_dir(&repo).unwrap();
+    git(&repo, &["init", "-q", "-b", "main"]);
+    git(&repo, &["config", "user.name", "Test"]);
+    git(&repo, &["config", "user.email", "test@example.com"]);
+    git(&repo, &["config", "commit.gpgsign", "false"]);
+    commit_file(&repo, "a.txt", "a\n", "init");
+    let fork = git(&repo, &["rev-parse", "HEAD"]);
+
+    git(&repo, &["checkout", "-q", "-b", "feature"]);
+    commit_file(&repo, "f.txt", "1\n", "feat: first");
+    commit_file(&repo, "f.txt", "2\n", "feat: second");
+
+    // main moves on after the branch was cut
+    git(&repo, &["checkout", "-q", "main"]);
+    commit_file(&repo, "u.txt", "upstream\n", "fix: upstream");
+    git(&repo, &["checkout", "-q", "feature"]);
+
+    // Stand-in for the claude CLI
+    let bin = tmp.path().join("bin");
+    std::fs::create_dir(&bin).unwrap();
+    let claude = bin.join("claude");
+    std::fs::write(&claude, "#!/bin/sh\necho 'feat: add f'\n").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&claude, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    let path = std::env::join_paths(
+        std::iter::once(bin).chain(std::env::split_paths(&std::env::var_os("PATH").unwrap())),
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ccmt"))
+        .current_dir(&repo)
+        .env("PATH", path)
+        .env("HOME", tmp.path())
+        .env("XDG_CONFIG_HOME", tmp.path().join("config"))
+        .args(["--no-confirm", "squash", "main"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    assert_eq!(git(&repo, &["rev-parse", "HEAD~1"]), fork);
+    assert_eq!(git(&repo, &["log", "-1", "--format=%s"]), "feat: add f");
+    assert_eq!(git(&repo, &["show", "HEAD:f.txt"]), "2");
+    // The squash commit must not undo the upstream change
+    assert_eq!(
+        git(&repo, &["diff", "--name-only", "HEAD~1", "HEAD"]),
+        "f.txt"
+    );
+}